@@ -4,6 +4,9 @@ var filesToCache = [
   './index.html',
   './noise-functions-demo.js',
   './noise-functions-demo_bg.wasm',
+  './worker_loader.js',
+  './worker.js',
+  './worker_bg.wasm',
 ];
 
 /* Start the service worker and cache all of the app's content */
@@ -39,13 +39,13 @@ fn main() {
     let path = |file: &str| format!("assets/{file}");
 
     let create_ico = |file: &str, size: usize| {
-        noise_to_image(&ico_noise, size, size)
+        noise_to_image(ico_noise, size, size)
             .save(path(file))
             .unwrap()
     };
 
     let create_png = |file: &str, size: usize| {
-        let image = noise_to_image(&png_noise, size, size);
+        let image = noise_to_image(png_noise, size, size);
         std::fs::write(path(file), png(image)).unwrap()
     };
 
@@ -0,0 +1,289 @@
+//! Animates the z and w slice of 3D and 4D noise.
+
+use eframe::egui;
+
+use crate::settings::{Dimension, Settings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoopMode {
+    None,
+    Repeat,
+    PingPong,
+}
+
+impl LoopMode {
+    pub const VARIANTS: &'static [Self] = &[Self::None, Self::Repeat, Self::PingPong];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            LoopMode::None => "None",
+            LoopMode::Repeat => "Repeat",
+            LoopMode::PingPong => "Ping Pong",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationMode {
+    /// Moves z and / or w at a constant speed.
+    Linear,
+    /// Moves z and w in a circle, sampling 4D noise so the animation loops seamlessly.
+    Circle,
+}
+
+impl AnimationMode {
+    pub const VARIANTS: &'static [Self] = &[Self::Linear, Self::Circle];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            AnimationMode::Linear => "Linear",
+            AnimationMode::Circle => "Circle in Z / W",
+        }
+    }
+}
+
+/// Returns the z and w at `t` of a loop around `center`, `t` being in `0.0..1.0`.
+///
+/// Sampling 4D noise along this circle gives an animation that loops seamlessly.
+pub fn circle_zw(center: [f32; 2], radius: f32, t: f32) -> [f32; 2] {
+    let (sin, cos) = (t * std::f32::consts::TAU).sin_cos();
+    [center[0] + radius * cos, center[1] + radius * sin]
+}
+
+pub struct Animation {
+    playing: bool,
+    mode: AnimationMode,
+    animate_z: bool,
+    animate_w: bool,
+    /// Change of z / w per second.
+    speed: f32,
+    loop_mode: LoopMode,
+    /// Length of a loop in seconds, also the period of the circle.
+    loop_duration: f32,
+    /// Radius of the circle.
+    radius: f32,
+    /// Seconds played since the last reset.
+    time: f32,
+    /// The z and w at `time` zero, the center of the circle.
+    origin: [f32; 2],
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            mode: AnimationMode::Linear,
+            animate_z: true,
+            animate_w: false,
+            speed: 0.2,
+            loop_mode: LoopMode::None,
+            loop_duration: 5.0,
+            radius: 0.5,
+            time: 0.0,
+            origin: [0.0; 2],
+        }
+    }
+}
+
+impl Animation {
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Advances the animation by a frame. Returns `true` if the settings changed.
+    pub fn update(&mut self, ctx: &egui::Context, settings: &mut Settings) -> bool {
+        if !self.playing {
+            return false;
+        }
+
+        if self.mode == AnimationMode::Linear && settings.dimension == Dimension::D2 {
+            self.playing = false;
+            return false;
+        }
+
+        // don't jump ahead after a long frame
+        self.time += ctx.input(|i| i.stable_dt).min(0.1);
+        self.apply(settings);
+        ctx.request_repaint();
+        true
+    }
+
+    /// Returns how far z and w have moved from the origin.
+    fn offset(&self) -> [f32; 2] {
+        let duration = self.loop_duration;
+
+        if self.mode == AnimationMode::Circle {
+            let t = if duration <= 0.0 {
+                0.0
+            } else {
+                self.time / duration
+            };
+
+            return circle_zw([0.0; 2], self.radius, t);
+        }
+
+        let time = match self.loop_mode {
+            _ if duration <= 0.0 => self.time,
+            LoopMode::None => self.time,
+            LoopMode::Repeat => self.time.rem_euclid(duration),
+            LoopMode::PingPong => {
+                let time = self.time.rem_euclid(duration * 2.0);
+                if time > duration {
+                    duration * 2.0 - time
+                } else {
+                    time
+                }
+            }
+        };
+
+        let offset = time * self.speed;
+        [
+            if self.animate_z { offset } else { 0.0 },
+            if self.animate_w { offset } else { 0.0 },
+        ]
+    }
+
+    fn apply(&self, settings: &mut Settings) {
+        let [z, w] = self.offset();
+
+        match self.mode {
+            AnimationMode::Linear => {
+                if self.animate_z {
+                    settings.z = self.origin[0] + z;
+                }
+
+                if self.animate_w && settings.dimension == Dimension::D4 {
+                    settings.w = self.origin[1] + w;
+                }
+            }
+            AnimationMode::Circle => {
+                settings.dimension = Dimension::D4;
+                settings.z = self.origin[0] + z;
+                settings.w = self.origin[1] + w;
+            }
+        }
+    }
+
+    fn play(&mut self, settings: &Settings) {
+        // continue from the current slice in case it was changed while paused
+        let [z, w] = self.offset();
+        self.origin = [settings.z - z, settings.w - w];
+        self.playing = true;
+    }
+
+    /// Shows the animation controls. Returns `true` if the settings changed.
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+        let mut changed = false;
+
+        egui::CollapsingHeader::new("Animation").show(ui, |ui| {
+            let can_play =
+                self.mode == AnimationMode::Circle || settings.dimension != Dimension::D2;
+
+            ui.horizontal(|ui| {
+                if self.playing {
+                    if ui.button("⏸ Pause").clicked() {
+                        self.playing = false;
+                    }
+                } else if ui
+                    .add_enabled(can_play, egui::Button::new("▶ Play"))
+                    .on_disabled_hover_text("Linear animation requires 3D or 4D noise.")
+                    .clicked()
+                {
+                    self.play(settings);
+                }
+
+                if ui.button("⏮ Reset").clicked() {
+                    if !self.playing {
+                        self.play(settings);
+                        self.playing = false;
+                    }
+
+                    self.time = 0.0;
+                    self.apply(settings);
+                    changed = true;
+                }
+            });
+
+            egui::Grid::new("animation").num_columns(2).show(ui, |ui| {
+                ui.label("Mode");
+                egui::ComboBox::from_id_salt("animation mode")
+                    .selected_text(self.mode.to_str())
+                    .show_ui(ui, |ui| {
+                        for &mode in AnimationMode::VARIANTS {
+                            if ui
+                                .selectable_value(&mut self.mode, mode, mode.to_str())
+                                .changed()
+                            {
+                                // the origin means something else in the other mode
+                                self.time = 0.0;
+                                self.origin = [settings.z, settings.w];
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                if self.mode == AnimationMode::Circle {
+                    ui.label("Radius");
+                    ui.add(
+                        egui::DragValue::new(&mut self.radius)
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX),
+                    );
+                    ui.end_row();
+
+                    ui.label("Period");
+                    ui.add(
+                        egui::DragValue::new(&mut self.loop_duration)
+                            .speed(0.05)
+                            .range(0.1..=600.0)
+                            .suffix(" s"),
+                    );
+                    ui.end_row();
+
+                    return;
+                }
+
+                ui.label("Animate");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.animate_z, "Z");
+
+                    if settings.dimension == Dimension::D4 {
+                        ui.checkbox(&mut self.animate_w, "W");
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Speed");
+                ui.add(
+                    egui::DragValue::new(&mut self.speed)
+                        .speed(0.01)
+                        .suffix("/s"),
+                );
+                ui.end_row();
+
+                ui.label("Loop");
+                egui::ComboBox::from_id_salt("loop mode")
+                    .selected_text(self.loop_mode.to_str())
+                    .show_ui(ui, |ui| {
+                        for &mode in LoopMode::VARIANTS {
+                            ui.selectable_value(&mut self.loop_mode, mode, mode.to_str());
+                        }
+                    });
+                ui.end_row();
+
+                if self.loop_mode != LoopMode::None {
+                    ui.label("Loop Duration");
+                    ui.add(
+                        egui::DragValue::new(&mut self.loop_duration)
+                            .speed(0.05)
+                            .range(0.1..=600.0)
+                            .suffix(" s"),
+                    );
+                    ui.end_row();
+                }
+            });
+        });
+
+        changed
+    }
+}
@@ -1,130 +1,167 @@
-use std::hash::Hash;
-
-use web_time::{Duration, Instant};
+use web_time::Duration;
 
 use eframe::egui;
-use noise_functions_config::{Config, Improve, Modifier, Noise, noise_functions::Noise as _};
+use noise_functions_config::{Improve, Modifier, Noise};
+
+use crate::{
+    animation::Animation,
+    code,
+    colormap::{self, Colormap, Mask},
+    comparison::Comparison,
+    contours,
+    cross_section::CrossSection,
+    export::{AnimationExport, PngExport},
+    heightmap::HeightmapView,
+    history::History,
+    inspector::Inspector,
+    presets::Presets,
+    preview,
+    render::{Cache, Render, Renderer},
+    sampling,
+    settings::{DEFAULT_CONFIG, DEFAULT_SETTINGS, Dimension, Settings, View},
+    settings_file::SettingsFile,
+    shading::Shading,
+    simd_diff::SimdDiff,
+};
+
+const SETTINGS_KEY: &str = "settings";
+
+#[cfg(target_arch = "wasm32")]
+const URL_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct App {
     settings: Settings,
     texture: egui::TextureHandle,
     changed: bool,
-    elapsed: Duration,
+    timing: Timing,
     sample_success: bool,
-
-    // we cache the vecs so we don't need to allocate them each update
-    cache: Cache,
-}
-
-struct Settings {
-    config: Config,
-    texture_size: usize,
-    dimension: Dimension,
-    x: f32,
-    y: f32,
-    z: f32,
-    w: f32,
-    simd: bool,
-    show_tiles: bool,
-    link_tile_size_to_frequency: bool,
+    png_export: PngExport,
+    animation_export: AnimationExport,
+    warning: Option<String>,
+
+    #[cfg(target_arch = "wasm32")]
+    url_fragment: String,
+    #[cfg(target_arch = "wasm32")]
+    url_updated: web_time::Instant,
+
+    renderer: Renderer,
+    cache: Option<Cache>,
+    inspector: Inspector,
+    animation: Animation,
+    heightmap: HeightmapView,
+    cross_section: CrossSection,
+    comparison: Comparison,
+    simd_diff: SimdDiff,
+    history: History,
+    presets: Presets,
+    settings_file: SettingsFile,
 }
 
-#[derive(Default)]
-struct Cache {
-    values: Vec<f32>,
-    pixels: Vec<egui::Color32>,
-    size: usize,
-}
+impl App {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let settings = cc
+            .storage
+            .and_then(|storage| storage.get_string(SETTINGS_KEY))
+            .and_then(|text| match Settings::from_text(&text) {
+                Ok(settings) => Some(settings),
+                Err(e) => {
+                    log::warn!("failed to restore settings: {e}");
+                    None
+                }
+            })
+            .unwrap_or(DEFAULT_SETTINGS);
 
-impl Cache {
-    fn resize(&mut self, new_size: usize) {
-        if new_size == self.size {
-            return;
+        Self {
+            settings,
+            texture: cc.egui_ctx.load_texture(
+                "noise",
+                egui::ColorImage::example(),
+                egui::TextureOptions::NEAREST,
+            ),
+            changed: true,
+            timing: Default::default(),
+            renderer: Default::default(),
+            cache: None,
+            inspector: Default::default(),
+            animation: Default::default(),
+            heightmap: Default::default(),
+            cross_section: Default::default(),
+            comparison: Default::default(),
+            simd_diff: Default::default(),
+            history: Default::default(),
+            presets: Presets::load(cc.storage),
+            settings_file: Default::default(),
+            sample_success: true,
+            png_export: Default::default(),
+            animation_export: Default::default(),
+            warning: None,
+            #[cfg(target_arch = "wasm32")]
+            url_fragment: String::new(),
+            #[cfg(target_arch = "wasm32")]
+            url_updated: web_time::Instant::now(),
         }
-
-        self.values = Vec::new();
-        self.pixels = Vec::new();
-        self.values.resize(new_size, 0.0);
-        self.pixels
-            .resize(new_size, egui::Color32::from_rgb(255, 0, 255));
-        self.size = new_size;
     }
-}
 
-const DEFAULT_CONFIG: Config = Config {
-    noise: Noise::OpenSimplex2,
-    seed: 0,
-    frequency: 3.0,
+    /// Creates the app with the settings of a shared link.
+    ///
+    /// Falls back to the default settings and shows a warning if the fragment is malformed.
+    pub fn from_url_fragment(cc: &eframe::CreationContext<'_>, fragment: &str) -> Self {
+        let mut app = Self::new(cc);
+        let fragment = fragment.trim_start_matches('#');
 
-    // modifiers
-    modifier: Modifier::None,
-    triangle_wave_frequency: 2.0,
+        if fragment.is_empty() {
+            return app;
+        }
 
-    // fractal
-    fractal: false,
-    lacunarity: 2.0,
-    octaves: 3,
-    gain: 0.5,
-    weighted_strength: 0.0,
+        match Settings::from_url_fragment(fragment) {
+            Ok(settings) => app.settings = settings,
+            Err(e) => {
+                app.settings = DEFAULT_SETTINGS;
+                app.warning = Some(format!("Invalid link, using the default settings: {e}"));
+            }
+        }
 
-    // open simplex 2
-    improve: Improve::None,
+        app
+    }
 
-    // cell
-    jitter: 1.0,
+    /// Keeps the url fragment in sync with the settings so the current noise can be shared by link.
+    ///
+    /// Browsers limit how often the url can be replaced, so this waits until a drag is done and
+    /// updates it at most once per [`URL_UPDATE_INTERVAL`].
+    #[cfg(target_arch = "wasm32")]
+    fn update_url_fragment(&mut self, ctx: &egui::Context) {
+        use eframe::{wasm_bindgen::JsValue, web_sys};
 
-    // tiling
-    tileable: false,
-    tile_width: 3.0,
-    tile_height: 3.0,
-};
+        if ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
 
-const DEFAULT_SETTINGS: Settings = Settings {
-    config: DEFAULT_CONFIG,
-    texture_size: 295,
-    dimension: Dimension::D2,
-    x: 0.0,
-    y: 0.0,
-    z: 0.0,
-    w: 0.0,
-    simd: false,
-    show_tiles: true,
-    link_tile_size_to_frequency: true,
-};
+        let fragment = self.settings.to_url_fragment();
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Dimension {
-    D2,
-    D3,
-    D4,
-}
+        if fragment == self.url_fragment {
+            return;
+        }
 
-impl Dimension {
-    pub const VARIANTS: &'static [Self] = &[Self::D2, Self::D3, Self::D4];
+        let elapsed = self.url_updated.elapsed();
 
-    pub fn to_str(self) -> &'static str {
-        match self {
-            Dimension::D2 => "2D",
-            Dimension::D3 => "3D",
-            Dimension::D4 => "4D",
+        if elapsed < URL_UPDATE_INTERVAL {
+            // try again once the interval is over, even if nothing else changes
+            ctx.request_repaint_after(URL_UPDATE_INTERVAL - elapsed);
+            return;
         }
-    }
-}
 
-impl App {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        Self {
-            settings: DEFAULT_SETTINGS,
-            texture: cc.egui_ctx.load_texture(
-                "noise",
-                egui::ColorImage::example(),
-                egui::TextureOptions::NEAREST,
-            ),
-            changed: true,
-            elapsed: Duration::from_nanos(0),
-            cache: Default::default(),
-            sample_success: true,
+        let url = format!("#{fragment}");
+
+        let result = web_sys::window()
+            .and_then(|window| window.history().ok())
+            .map(|history| history.replace_state_with_url(&JsValue::NULL, "", Some(&url)));
+
+        if let Some(Err(e)) = result {
+            log::warn!("failed to update url: {e:?}");
         }
+
+        self.url_fragment = fragment;
+        self.url_updated = web_time::Instant::now();
     }
 
     pub fn settings_panel_contents(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
@@ -137,12 +174,31 @@ impl App {
                     z,
                     w,
                     simd,
+                    multithreaded,
                     show_tiles,
                     link_tile_size_to_frequency,
                     dimension,
                     texture_size,
+                    colormap,
+                    gradient,
+                    highlight_out_of_range,
+                    view,
+                    height_scale,
+                    shading,
+                    light_azimuth,
+                    light_elevation,
+                    contours,
+                    thresholds,
+                    mask,
                 },
             changed,
+            png_export,
+            animation_export,
+            warning,
+            inspector,
+            animation,
+            cache,
+            history,
             ..
         } = self;
 
@@ -158,6 +214,23 @@ impl App {
 
         ui.separator();
 
+        if let Some(text) = warning.as_deref() {
+            let mut dismissed = false;
+
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, text);
+                dismissed = ui.button("🗙").clicked();
+            });
+
+            if dismissed {
+                *warning = None;
+            }
+
+            ui.separator();
+        }
+
+        let restore = history.ui(ui);
+
         egui::Grid::new(0)
             .striped(true)
             .min_col_width(0.0)
@@ -475,161 +548,250 @@ impl App {
                         widget: egui::Checkbox::without_text,
                     },
                 );
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "Multithreaded",
+                        value: multithreaded,
+                        default: DEFAULT_SETTINGS.multithreaded,
+                        widget: egui::Checkbox::without_text,
+                    },
+                );
+
+                setting_separator(ui);
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "View",
+                        value: view,
+                        default: DEFAULT_SETTINGS.view,
+                        widget: combo_box!("view", View),
+                    },
+                );
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "Shading",
+                        value: shading,
+                        default: DEFAULT_SETTINGS.shading,
+                        widget: combo_box!("shading", Shading),
+                    },
+                );
+
+                if *view == View::Heightmap || *shading != Shading::None {
+                    setting(
+                        changed,
+                        ui,
+                        Setting {
+                            name: "Light Azimuth",
+                            value: light_azimuth,
+                            default: DEFAULT_SETTINGS.light_azimuth,
+                            widget: |v| egui::DragValue::new(v).range(0.0..=360.0).suffix("°"),
+                        },
+                    );
+
+                    setting(
+                        changed,
+                        ui,
+                        Setting {
+                            name: "Light Elevation",
+                            value: light_elevation,
+                            default: DEFAULT_SETTINGS.light_elevation,
+                            widget: |v| egui::DragValue::new(v).range(0.0..=90.0).suffix("°"),
+                        },
+                    );
+
+                    setting(
+                        changed,
+                        ui,
+                        Setting {
+                            name: "Height Scale",
+                            value: height_scale,
+                            default: DEFAULT_SETTINGS.height_scale,
+                            widget: |v| egui::DragValue::new(v).speed(0.01).range(0.0..=10.0),
+                        },
+                    );
+                }
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "Colormap",
+                        value: colormap,
+                        default: DEFAULT_SETTINGS.colormap,
+                        widget: combo_box!("colormap", Colormap),
+                    },
+                );
+
+                if *colormap == Colormap::Custom {
+                    setting(
+                        changed,
+                        ui,
+                        Setting {
+                            name: "Gradient",
+                            value: gradient,
+                            default: DEFAULT_SETTINGS.gradient,
+                            widget: colormap::gradient_editor,
+                        },
+                    );
+                }
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "Highlight Out of Range",
+                        value: highlight_out_of_range,
+                        default: DEFAULT_SETTINGS.highlight_out_of_range,
+                        widget: egui::Checkbox::without_text,
+                    },
+                );
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "Contours",
+                        value: contours,
+                        default: DEFAULT_SETTINGS.contours,
+                        widget: egui::Checkbox::without_text,
+                    },
+                );
+
+                setting(
+                    changed,
+                    ui,
+                    Setting {
+                        name: "Mask",
+                        value: mask,
+                        default: DEFAULT_SETTINGS.mask,
+                        widget: combo_box!("mask", Mask),
+                    },
+                );
+
+                if *contours || *mask != Mask::None {
+                    setting(
+                        changed,
+                        ui,
+                        Setting {
+                            name: "Thresholds",
+                            value: thresholds,
+                            default: DEFAULT_SETTINGS.thresholds,
+                            widget: contours::thresholds_editor,
+                        },
+                    );
+                }
             });
 
         ui.add_space(5.0);
+
+        let load = self.presets.ui(ui, &self.settings);
+        let opened = self.settings_file.ui(ui, &self.settings);
+        *changed |= animation.ui(ui, &mut self.settings);
+        png_export.ui(ui, &self.settings);
+        animation_export.ui(ui, &self.settings);
+        code::ui(ui, &self.settings);
+        self.simd_diff.ui(ui, &self.settings);
+
+        if let Some(cache) = cache {
+            inspector.ui(ui, &cache.settings, &cache.values);
+        }
+
+        if let Some(settings) = restore.or(load).or(opened) {
+            self.settings = settings;
+            self.changed = true;
+        }
+    }
+
+    /// Swaps A and B of the comparison, including their last render.
+    fn swap_comparison(&mut self) {
+        let Some(reference) = self.comparison.active() else {
+            return;
+        };
+
+        std::mem::swap(&mut self.settings, &mut reference.settings);
+        std::mem::swap(&mut self.texture, &mut reference.texture);
+        std::mem::swap(&mut self.cache, &mut reference.cache);
+
+        // restart both renders in case one is in progress
+        self.changed = true;
+        reference.changed = true;
     }
 
     pub fn image_preview_contents(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
+        if self.comparison.bar_ui(ui, &self.settings) {
+            self.swap_comparison();
+        }
+
         let Self {
             settings,
             texture,
             changed,
+            renderer,
             cache,
             ..
         } = self;
 
-        if *changed {
+        // while animating, let the render finish instead of restarting it every frame
+        if *changed && !(self.animation.is_playing() && renderer.is_busy()) {
             *changed = false;
+            renderer.start(settings);
+            self.timing.key = Timing::key(settings);
+        }
 
-            let size = settings.texture_size;
-            let z = settings.z;
-            let w = settings.w;
-
-            cache.resize(size * size);
-
-            let start = Instant::now();
-
-            fn sample(values: &mut [f32], settings: &Settings, f: impl Fn(f32, f32) -> f32) {
-                let Settings {
-                    config: Config { tileable, .. },
-                    texture_size: size,
-                    x: x_shift,
-                    y: y_shift,
-                    ..
-                } = *settings;
-
-                let scalar = 1.0 / size as f32;
-
-                if tileable {
-                    for y in 0..size {
-                        for x in 0..size {
-                            let i = y * size + x;
-                            let x = x as f32 * scalar + x_shift;
-                            let y = y as f32 * scalar + y_shift;
-                            values[i] = f(x, y);
-                        }
-                    }
-                } else {
-                    let scalar_times_two = scalar * 2.0;
-
-                    for y in 0..size {
-                        for x in 0..size {
-                            let i = y * size + x;
-                            let x = x as f32 * scalar_times_two - 1.0 + x_shift;
-                            let y = y as f32 * scalar_times_two - 1.0 + y_shift;
-                            values[i] = f(x, y);
-                        }
-                    }
-                }
-            }
+        if let Some(render) = renderer.poll() {
+            self.sample_success = render.success;
+            self.timing.finish(&render);
 
-            let sampled: bool = if settings.simd {
-                match settings.dimension {
-                    Dimension::D2 => {
-                        if let Some(sampler) = settings.config.sampler2a() {
-                            sample(&mut cache.values, settings, |x, y| sampler.sample2a([x, y]));
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    Dimension::D3 => {
-                        if let Some(sampler) = settings.config.sampler3a() {
-                            sample(&mut cache.values, settings, |x, y| {
-                                sampler.sample3a([x, y, z, 0.0])
-                            });
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    Dimension::D4 => {
-                        if let Some(sampler) = settings.config.sampler4a() {
-                            sample(&mut cache.values, settings, |x, y| {
-                                sampler.sample4a([x, y, z, w])
-                            });
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                }
-            } else {
-                match settings.dimension {
-                    Dimension::D2 => {
-                        if let Some(sampler) = settings.config.sampler2() {
-                            sample(&mut cache.values, settings, |x, y| sampler.sample2([x, y]));
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    Dimension::D3 => {
-                        if let Some(sampler) = settings.config.sampler3() {
-                            sample(&mut cache.values, settings, |x, y| {
-                                sampler.sample3([x, y, z])
-                            });
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    Dimension::D4 => {
-                        if let Some(sampler) = settings.config.sampler4() {
-                            sample(&mut cache.values, settings, |x, y| {
-                                sampler.sample4([x, y, z, w])
-                            });
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                }
-            };
-
-            self.sample_success = sampled;
-            self.elapsed = start.elapsed();
-
-            for x in 0..size {
-                for y in 0..size {
-                    let i = x * size + y;
-                    let value = cache.values[i];
-                    let value_01 = value * 0.5 + 0.5;
-                    let value_255 = (value_01 * 255.0) as u8;
-                    let color = egui::Color32::from_gray(value_255);
-                    cache.pixels[i] = color;
-                }
-            }
+            *cache = Some(render.upload(texture));
+        }
 
-            texture.set(
-                egui::ColorImage {
-                    size: [settings.texture_size; 2],
-                    pixels: cache.pixels.clone(),
-                },
-                egui::TextureOptions::NEAREST,
-            );
+        if let Some(reference) = self.comparison.active() {
+            reference.update(ui.ctx());
         }
 
         let size = texture.size_vec2();
 
-        if self.settings.show_tiles && self.settings.config.tileable && self.sample_success {
-            egui::Grid::new("image grid")
+        let mut images = Vec::new();
+
+        let preview_rect = if self.settings.view == View::Heightmap {
+            let desired_size = egui::Vec2::splat(size.max_elem().max(256.0));
+
+            match &self.cache {
+                Some(cache) => {
+                    self.heightmap
+                        .ui(
+                            ui,
+                            &cache.settings,
+                            &cache.values,
+                            self.settings.height_scale,
+                            desired_size,
+                        )
+                        .rect
+                }
+                None => ui.allocate_space(desired_size).1,
+            }
+        } else if let Some((rect, image)) = self.comparison.images_ui(ui, texture, size) {
+            images.push(image);
+            rect
+        } else if self.settings.show_tiles && self.settings.config.tileable && self.sample_success {
+            let grid = egui::Grid::new("image grid")
                 .spacing([0.0; 2])
                 .show(ui, |ui| {
                     for i in 0..4 {
                         let sized_texture = egui::load::SizedTexture::new(&mut *texture, size);
-                        let image = ui.add(egui::Image::new(sized_texture).fit_to_exact_size(size));
+                        let image = ui.add(
+                            egui::Image::new(sized_texture)
+                                .fit_to_exact_size(size)
+                                .sense(egui::Sense::click_and_drag()),
+                        );
 
                         ui.painter()
                             .circle_filled(image.rect.center(), 40.0, egui::Color32::BLACK);
@@ -652,11 +814,19 @@ impl App {
                         if i % 2 != 0 {
                             ui.end_row();
                         }
+
+                        images.push(image);
                     }
                 });
+
+            grid.response.rect
         } else {
             let sized_texture = egui::load::SizedTexture::new(&mut *texture, size);
-            let image = ui.add(egui::Image::new(sized_texture).fit_to_exact_size(size));
+            let image = ui.add(
+                egui::Image::new(sized_texture)
+                    .fit_to_exact_size(size)
+                    .sense(egui::Sense::click_and_drag()),
+            );
 
             if !self.sample_success {
                 let image_rect = egui::Rect::from_min_size(image.rect.left_top(), size);
@@ -665,7 +835,7 @@ impl App {
                     if self.settings.config.tileable && self.settings.dimension != Dimension::D2 {
                         "tileable is only available in 2D"
                     } else {
-                        "dimension/tileable not available for this noise type"
+                        sampling::UNSUPPORTED
                     };
 
                 let galley = ui.painter().layout_job(egui::text::LayoutJob {
@@ -705,10 +875,140 @@ impl App {
                     egui::Color32::DEBUG_COLOR,
                 );
             }
+
+            let image_rect = image.rect;
+            images.push(image);
+            image_rect
+        };
+
+        for image in &images {
+            if let Some(reference) = self.comparison.linked() {
+                let changed = preview::pan_and_zoom(
+                    &mut [&mut self.settings, &mut reference.settings],
+                    image,
+                );
+                self.changed |= changed;
+                reference.changed |= changed;
+            } else {
+                self.changed |= preview::pan_and_zoom(&mut [&mut self.settings], image);
+            }
+
+            if let Some(cache) = &self.cache {
+                contours::paint(
+                    ui.painter(),
+                    image.rect,
+                    cache.settings.texture_size,
+                    &cache.contours,
+                );
+
+                self.inspector
+                    .interact(image, &cache.settings, &cache.values);
+                self.inspector
+                    .paint_probes(ui.painter(), image.rect, cache.settings.texture_size);
+
+                self.cross_section
+                    .interact(image, cache.settings.texture_size);
+                self.cross_section
+                    .paint(ui.painter(), image.rect, cache.settings.texture_size);
+            }
+        }
+
+        if let Some(cache) = &self.cache {
+            ui.add_space(5.0);
+
+            if let Some(stats) = &cache.stats {
+                stats.ui(ui, preview_rect.width().max(200.0));
+            }
+
+            if cache.settings.highlight_out_of_range {
+                preview::out_of_range_legend(ui);
+            }
+
+            ui.add_space(5.0);
+            self.cross_section
+                .ui(ui, &cache.settings, preview_rect.width().max(200.0));
+        }
+
+        if let Some(progress) = self.renderer.progress() {
+            // keep polling until the render is done
+            ui.ctx().request_repaint();
+
+            let bar_rect = egui::Rect::from_min_size(
+                preview_rect.left_top() + egui::vec2(5.0, 5.0),
+                egui::vec2((preview_rect.width() - 10.0).max(0.0), 16.0),
+            );
+
+            ui.put(bar_rect, egui::ProgressBar::new(progress).show_percentage());
         }
     }
 }
 
+/// Timing of the last render, used to compare the throughput of scalar, simd and threaded sampling.
+#[derive(Default)]
+struct Timing {
+    elapsed: Duration,
+    samples: usize,
+    threads: usize,
+    /// Identifies the settings of the render apart from threading.
+    key: String,
+    /// Elapsed time of the last single threaded render.
+    single_threaded: Option<(String, Duration)>,
+}
+
+impl Timing {
+    fn key(settings: &Settings) -> String {
+        let mut settings = settings.clone();
+        settings.multithreaded = false;
+        settings.to_text()
+    }
+
+    fn finish(&mut self, render: &Render) {
+        self.elapsed = render.elapsed;
+        self.samples = render.size * render.size;
+        self.threads = render.threads;
+
+        if render.threads == 1 {
+            self.single_threaded = Some((self.key.clone(), render.elapsed));
+        }
+    }
+
+    /// Returns how much faster the last render was compared to the single threaded one.
+    fn speedup(&self) -> Option<f64> {
+        let (key, single_threaded) = self.single_threaded.as_ref()?;
+
+        if self.threads <= 1 || *key != self.key || self.elapsed.is_zero() {
+            return None;
+        }
+
+        Some(single_threaded.as_secs_f64() / self.elapsed.as_secs_f64())
+    }
+}
+
+impl std::fmt::Display for Timing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "elapsed: {:?}", self.elapsed)?;
+
+        if self.elapsed.is_zero() {
+            return Ok(());
+        }
+
+        let samples_per_second = self.samples as f64 / self.elapsed.as_secs_f64();
+        write!(
+            f,
+            "\n{:.1} Msamples/s, {} thread{}",
+            samples_per_second / 1_000_000.0,
+            self.threads,
+            if self.threads == 1 { "" } else { "s" }
+        )?;
+
+        if let Some(speedup) = self.speedup() {
+            write!(f, ", {speedup:.1}x")?;
+        }
+
+        Ok(())
+    }
+}
+
 pub fn is_mobile(ctx: &egui::Context) -> bool {
     let screen_size = ctx.screen_rect().size();
     screen_size.x < 550.0
@@ -717,9 +1017,21 @@ pub fn is_mobile(ctx: &egui::Context) -> bool {
 const COMBO_BOX_WIDTH: f32 = 150.0;
 
 impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(SETTINGS_KEY, self.settings.to_text());
+        self.presets.save(storage);
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         let is_mobile = is_mobile(ctx);
 
+        if let Some(settings) = self.history.shortcuts(ctx) {
+            self.settings = settings;
+            self.changed = true;
+        }
+
+        self.changed |= self.animation.update(ctx, &mut self.settings);
+
         egui::SidePanel::left("settings_panel")
             .resizable(false)
             .max_width(325.0)
@@ -734,9 +1046,7 @@ impl eframe::App for App {
                         ui.separator();
                     }
 
-                    ui.add(
-                        egui::Label::new(format!("elapsed: {:?}", self.elapsed)).selectable(false),
-                    );
+                    ui.add(egui::Label::new(self.timing.to_string()).selectable(false));
                 });
             });
 
@@ -789,6 +1099,14 @@ impl eframe::App for App {
 
                 ui.add(egui::Label::new(label).selectable(false));
             });
+
+        // the animation would add an entry each frame
+        if !self.animation.is_playing() {
+            self.history.record(ctx, &self.settings);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        self.update_url_fragment(ctx);
     }
 }
 
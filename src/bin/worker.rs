@@ -0,0 +1,10 @@
+//! The web worker that samples the noise for the web version, see `index.html`.
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    noise_functions_demo::worker::run();
+}
+
+/// Only the web version uses workers, natively the noise is sampled on threads.
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {}
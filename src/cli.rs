@@ -0,0 +1,110 @@
+//! Renders noise to an image without opening a window.
+
+use crate::{
+    export,
+    settings::{DEFAULT_SETTINGS, Settings},
+};
+
+const USAGE: &str = "\
+Usage: noise-functions-demo render [OPTIONS]
+
+Options:
+  -o, --output <FILE>    Image file to write [default: noise.png]
+      --size <PIXELS>    Width and height of the image [default: 1024]
+      --config <FILE>    TOML settings file, as saved by the app
+      --<KEY> <VALUE>    Overrides a setting, applied after the config file
+  -h, --help             Print help
+
+Settings:";
+
+/// Settings that only affect the app window and not the rendered image.
+const WINDOW_ONLY_SETTINGS: &[&str] = &[
+    "texture_size",
+    "show_tiles",
+    "link_tile_size_to_frequency",
+    "view",
+    "contours",
+];
+
+/// Runs the `render` subcommand with the arguments that follow it.
+pub fn run(args: impl IntoIterator<Item = String>) -> Result<(), String> {
+    let mut output = String::from("noise.png");
+    let mut size = 1024;
+    let mut config = None;
+    let mut overrides = Vec::new();
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+
+        if flag == "-h" || flag == "--help" {
+            print_usage();
+            return Ok(());
+        }
+
+        let Some(name) = flag
+            .strip_prefix("--")
+            .or_else(|| flag.strip_prefix('-').filter(|name| *name == "o"))
+        else {
+            return Err(format!("unexpected argument `{flag}`, see --help"));
+        };
+
+        let value = inline_value
+            .or_else(|| args.next())
+            .ok_or_else(|| format!("missing value for `{flag}`"))?;
+
+        match name {
+            "o" | "output" => output = value,
+            "size" => {
+                size = value
+                    .parse()
+                    .ok()
+                    .filter(|size| (1..=export::MAX_EXPORT_SIZE).contains(size))
+                    .ok_or_else(|| {
+                        format!("size must be between 1 and {}", export::MAX_EXPORT_SIZE)
+                    })?
+            }
+            "config" => config = Some(value),
+            _ => overrides.push((name.replace('-', "_"), value)),
+        }
+    }
+
+    let mut settings = match config {
+        Some(path) => {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read {path:?}: {e}"))?;
+            Settings::from_file(&text).map_err(|e| format!("{path}: {e}"))?
+        }
+        None => DEFAULT_SETTINGS,
+    };
+
+    for (key, value) in overrides {
+        if WINDOW_ONLY_SETTINGS.contains(&key.as_str()) {
+            return Err(format!("`--{key}` only affects the app window, see --help"));
+        }
+
+        settings.set_field(&key, &value)?;
+    }
+
+    let png = export::render_png(&settings, size)?;
+    export::save_file(output.as_ref(), "image/png", &png)?;
+
+    println!("wrote {output}");
+    Ok(())
+}
+
+fn print_usage() {
+    println!("{USAGE}");
+
+    for (key, default) in DEFAULT_SETTINGS.fields() {
+        if WINDOW_ONLY_SETTINGS.contains(&key) {
+            continue;
+        }
+
+        println!("  --{key} [default: {default}]");
+    }
+}
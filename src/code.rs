@@ -0,0 +1,301 @@
+//! Generates Rust code for the current settings.
+
+use eframe::egui;
+use noise_functions_config::{Config, Improve, Modifier, Noise};
+
+use crate::{
+    sampling,
+    settings::{Dimension, Settings},
+};
+
+/// Returns the `noise_functions` expression equivalent to the settings, followed by how to sample it.
+///
+/// The methods are called in the same order as `noise_functions_config::Config` builds its sampler.
+pub fn noise_functions_code(settings: &Settings) -> String {
+    let config = &settings.config;
+
+    let Config {
+        noise,
+        seed,
+        frequency,
+        modifier,
+        triangle_wave_frequency,
+        fractal,
+        lacunarity,
+        octaves,
+        gain,
+        weighted_strength,
+        improve,
+        jitter,
+        tileable,
+        tile_width,
+        tile_height,
+    } = *config;
+
+    let supported = match settings.dimension {
+        Dimension::D2 => config.sampler2().is_some(),
+        Dimension::D3 => config.sampler3().is_some(),
+        Dimension::D4 => config.sampler4().is_some(),
+    };
+
+    if !supported {
+        return format!("// {}", sampling::UNSUPPORTED);
+    }
+
+    let mut code = match noise {
+        Noise::CellValue | Noise::CellDistance | Noise::CellDistanceSq => {
+            format!(
+                "let noise = {}::default()\n    .jitter({jitter:?})",
+                noise_type(noise)
+            )
+        }
+        _ => format!("let noise = {}", noise_type(noise)),
+    };
+
+    if let Some(method) = improve_method(improve)
+        .filter(|_| matches!(noise, Noise::OpenSimplex2 | Noise::OpenSimplex2s))
+    {
+        code.push_str(&format!("\n    .{method}()"));
+    }
+
+    if tileable {
+        code.push_str(&format!("\n    .tileable({tile_width:?}, {tile_height:?})"));
+    }
+
+    match modifier {
+        Modifier::None => (),
+        Modifier::Ridged => code.push_str("\n    .ridged()"),
+        Modifier::TriangleWave => code.push_str(&format!(
+            "\n    .triangle_wave({triangle_wave_frequency:?})"
+        )),
+    }
+
+    if fractal {
+        code.push_str(&format!("\n    .fbm({octaves}, {gain:?}, {lacunarity:?})"));
+
+        if weighted_strength != 0.0 {
+            code.push_str(&format!("\n    .weighted({weighted_strength:?})"));
+        }
+    }
+
+    code.push_str(&format!("\n    .seed({seed})"));
+    code.push_str(&format!("\n    .frequency({frequency:?});\n\n"));
+
+    let Settings { z, w, .. } = *settings;
+
+    code.push_str(&match settings.dimension {
+        Dimension::D2 => "let value = noise.sample2([x, y]);".to_string(),
+        Dimension::D3 => format!("let value = noise.sample3([x, y, {z:?}]);"),
+        Dimension::D4 => format!("let value = noise.sample4([x, y, {z:?}, {w:?}]);"),
+    });
+
+    code
+}
+
+/// Returns the name of the `noise_functions` type of the noise.
+fn noise_type(noise: Noise) -> &'static str {
+    match noise {
+        Noise::Value => "Value",
+        Noise::ValueCubic => "ValueCubic",
+        Noise::Perlin => "Perlin",
+        Noise::Simplex => "Simplex",
+        Noise::OpenSimplex2 => "OpenSimplex2",
+        Noise::OpenSimplex2s => "OpenSimplex2s",
+        Noise::CellValue => "CellValue",
+        Noise::CellDistance => "CellDistance",
+        Noise::CellDistanceSq => "CellDistanceSq",
+    }
+}
+
+/// Returns the name of the `Improve` variant, its `Debug` output is the display name.
+fn improve_variant(improve: Improve) -> &'static str {
+    match improve {
+        Improve::None => "None",
+        Improve::X => "X",
+        Improve::Xy => "Xy",
+        Improve::Xz => "Xz",
+        Improve::Xyz => "Xyz",
+        Improve::XyzXy => "XyzXy",
+        Improve::XyzXz => "XyzXz",
+        Improve::XyZw => "XyZw",
+    }
+}
+
+/// Returns the `OpenSimplexNoise` method of the improvement.
+fn improve_method(improve: Improve) -> Option<&'static str> {
+    Some(match improve {
+        Improve::None => return None,
+        Improve::X => "improve2_x",
+        Improve::Xy => "improve3_xy",
+        Improve::Xz => "improve3_xz",
+        Improve::Xyz => "improve4_xyz",
+        Improve::XyzXy => "improve4_xyz_xy",
+        Improve::XyzXz => "improve4_xyz_xz",
+        Improve::XyZw => "improve4_xy_zw",
+    })
+}
+
+/// Returns the `noise_functions_config::Config` literal of the settings.
+pub fn config_code(settings: &Settings) -> String {
+    let Config {
+        noise,
+        seed,
+        frequency,
+        modifier,
+        triangle_wave_frequency,
+        fractal,
+        lacunarity,
+        octaves,
+        gain,
+        weighted_strength,
+        improve,
+        jitter,
+        tileable,
+        tile_width,
+        tile_height,
+    } = settings.config;
+
+    let noise = noise_type(noise);
+    let improve = improve_variant(improve);
+
+    format!(
+        "\
+Config {{
+    noise: Noise::{noise},
+    seed: {seed},
+    frequency: {frequency:?},
+
+    // modifiers
+    modifier: Modifier::{modifier:?},
+    triangle_wave_frequency: {triangle_wave_frequency:?},
+
+    // fractal
+    fractal: {fractal},
+    lacunarity: {lacunarity:?},
+    octaves: {octaves},
+    gain: {gain:?},
+    weighted_strength: {weighted_strength:?},
+
+    // open simplex 2
+    improve: Improve::{improve},
+
+    // cell
+    jitter: {jitter:?},
+
+    // tiling
+    tileable: {tileable},
+    tile_width: {tile_width:?},
+    tile_height: {tile_height:?},
+}}"
+    )
+}
+
+pub fn ui(ui: &mut egui::Ui, settings: &Settings) {
+    egui::CollapsingHeader::new("Copy as Rust").show(ui, |ui| {
+        let snippets = [
+            ("noise-functions", noise_functions_code(settings)),
+            ("noise-functions-config", config_code(settings)),
+        ];
+
+        for (name, code) in snippets {
+            ui.horizontal(|ui| {
+                ui.add(egui::Label::new(name).selectable(false));
+
+                if ui.button("📋 Copy").clicked() {
+                    ui.ctx().copy_text(code.clone());
+                }
+            });
+
+            ui.add(
+                egui::TextEdit::multiline(&mut code.as_str())
+                    .code_editor()
+                    .desired_width(f32::INFINITY),
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use noise_functions_config::{Improve, Modifier, Noise};
+
+    use super::*;
+    use crate::settings::DEFAULT_SETTINGS;
+
+    fn settings(f: impl FnOnce(&mut Settings)) -> Settings {
+        let mut settings = DEFAULT_SETTINGS;
+        f(&mut settings);
+        settings
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(
+            noise_functions_code(&DEFAULT_SETTINGS),
+            "\
+let noise = OpenSimplex2
+    .seed(0)
+    .frequency(3.0);
+
+let value = noise.sample2([x, y]);"
+        );
+    }
+
+    #[test]
+    fn improve() {
+        let settings = settings(|s| {
+            s.config.improve = Improve::Xy;
+            s.dimension = Dimension::D3;
+        });
+
+        assert_eq!(
+            noise_functions_code(&settings),
+            "\
+let noise = OpenSimplex2
+    .improve3_xy()
+    .seed(0)
+    .frequency(3.0);
+
+let value = noise.sample3([x, y, 0.0]);"
+        );
+
+        assert!(config_code(&settings).contains("improve: Improve::Xy,"));
+    }
+
+    #[test]
+    fn cell_tileable_ridged_fractal() {
+        let settings = settings(|s| {
+            s.config.noise = Noise::CellDistance;
+            s.config.jitter = 0.5;
+            s.config.tileable = true;
+            s.config.modifier = Modifier::Ridged;
+            s.config.fractal = true;
+            s.config.weighted_strength = 0.25;
+        });
+
+        assert_eq!(
+            noise_functions_code(&settings),
+            "\
+let noise = CellDistance::default()
+    .jitter(0.5)
+    .tileable(3.0, 3.0)
+    .ridged()
+    .fbm(3, 0.5, 2.0)
+    .weighted(0.25)
+    .seed(0)
+    .frequency(3.0);
+
+let value = noise.sample2([x, y]);"
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        let settings = settings(|s| {
+            s.config.tileable = true;
+            s.dimension = Dimension::D3;
+        });
+
+        assert!(noise_functions_code(&settings).starts_with("//"));
+    }
+}
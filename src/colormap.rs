@@ -0,0 +1,241 @@
+use eframe::egui::{self, Color32};
+
+use crate::fixed_list::{FixedList, FixedListEditor};
+
+/// Number of entries in a lookup table created by [`Colormap::lut`].
+pub const LUT_SIZE: usize = 256;
+
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// Highlight color of values above 1.
+pub const CLIPPED_HIGH_COLOR: Color32 = Color32::from_rgb(255, 40, 40);
+
+/// Highlight color of values below -1.
+pub const CLIPPED_LOW_COLOR: Color32 = Color32::from_rgb(40, 120, 255);
+
+/// Highlight color of NaN and infinite values.
+pub const NON_FINITE_COLOR: Color32 = Color32::from_rgb(255, 0, 255);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colormap {
+    Grayscale,
+    Viridis,
+    Magma,
+    Terrain,
+    Diverging,
+    Custom,
+}
+
+impl Colormap {
+    pub const VARIANTS: &'static [Self] = &[
+        Self::Grayscale,
+        Self::Viridis,
+        Self::Magma,
+        Self::Terrain,
+        Self::Diverging,
+        Self::Custom,
+    ];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Colormap::Grayscale => "Grayscale",
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Terrain => "Terrain",
+            Colormap::Diverging => "Diverging",
+            Colormap::Custom => "Custom",
+        }
+    }
+
+    fn stops(self, custom: &Gradient) -> &[GradientStop] {
+        match self {
+            Colormap::Grayscale => GRAYSCALE,
+            Colormap::Viridis => VIRIDIS,
+            Colormap::Magma => MAGMA,
+            Colormap::Terrain => TERRAIN,
+            Colormap::Diverging => DIVERGING,
+            Colormap::Custom => custom.as_slice(),
+        }
+    }
+
+    /// Creates a lookup table to be indexed with [`lut_index`].
+    pub fn lut(self, custom: &Gradient) -> [Color32; LUT_SIZE] {
+        let stops = self.stops(custom);
+        std::array::from_fn(|i| sample_stops(stops, i as f32 / (LUT_SIZE - 1) as f32))
+    }
+}
+
+/// Maps a noise value in `-1.0..=1.0` to an index into a [`Colormap::lut`].
+///
+/// Values outside of that range saturate, NaN maps to `0`.
+pub fn lut_index(value: f32) -> usize {
+    let value_01 = value * 0.5 + 0.5;
+    (value_01 * 255.0) as u8 as usize
+}
+
+/// Turns the noise into bands separated by the thresholds of the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mask {
+    None,
+    /// Alternates between black and white, a single threshold gives a binary mask.
+    TwoColor,
+    /// Gives each band a flat color of the colormap.
+    Banded,
+}
+
+impl Mask {
+    pub const VARIANTS: &'static [Self] = &[Self::None, Self::TwoColor, Self::Banded];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Mask::None => "None",
+            Mask::TwoColor => "Two Color",
+            Mask::Banded => "Banded",
+        }
+    }
+
+    /// Returns the color of each band from low to high, empty for [`Mask::None`].
+    pub fn band_colors(self, lut: &[Color32; LUT_SIZE], bands: usize) -> Vec<Color32> {
+        match self {
+            Mask::None => Vec::new(),
+            Mask::TwoColor => (0..bands)
+                .map(|i| {
+                    if i % 2 == 0 {
+                        Color32::BLACK
+                    } else {
+                        Color32::WHITE
+                    }
+                })
+                .collect(),
+            Mask::Banded => (0..bands)
+                .map(|i| lut[i * (LUT_SIZE - 1) / (bands - 1).max(1)])
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub position: f32,
+    pub color: Color32,
+}
+
+const fn stop(position: f32, rgb: u32) -> GradientStop {
+    GradientStop {
+        position,
+        color: Color32::from_rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8),
+    }
+}
+
+const GRAYSCALE: &[GradientStop] = &[stop(0.0, 0x000000), stop(1.0, 0xffffff)];
+
+const VIRIDIS: &[GradientStop] = &[
+    stop(0.0, 0x440154),
+    stop(0.125, 0x472c7a),
+    stop(0.25, 0x3b518b),
+    stop(0.375, 0x2c718e),
+    stop(0.5, 0x21908d),
+    stop(0.625, 0x27ad81),
+    stop(0.75, 0x5cc863),
+    stop(0.875, 0xaadc32),
+    stop(1.0, 0xfde725),
+];
+
+const MAGMA: &[GradientStop] = &[
+    stop(0.0, 0x000004),
+    stop(0.125, 0x1c1044),
+    stop(0.25, 0x4f127b),
+    stop(0.375, 0x812581),
+    stop(0.5, 0xb5367a),
+    stop(0.625, 0xe55964),
+    stop(0.75, 0xfb8761),
+    stop(0.875, 0xfec287),
+    stop(1.0, 0xfcfdbf),
+];
+
+const TERRAIN: &[GradientStop] = &[
+    stop(0.0, 0x333399),
+    stop(0.15, 0x0099ff),
+    stop(0.25, 0x00cc66),
+    stop(0.5, 0xffff99),
+    stop(0.75, 0x805c54),
+    stop(1.0, 0xffffff),
+];
+
+const DIVERGING: &[GradientStop] = &[
+    stop(0.0, 0x3b4cc0),
+    stop(0.25, 0x8db0fe),
+    stop(0.5, 0xdddddd),
+    stop(0.75, 0xf49a7b),
+    stop(1.0, 0xb40426),
+];
+
+/// Linearly interpolates between the two stops surrounding `t`.
+///
+/// The stops don't need to be sorted.
+fn sample_stops(stops: &[GradientStop], t: f32) -> Color32 {
+    let mut below: Option<&GradientStop> = None;
+    let mut above: Option<&GradientStop> = None;
+
+    for stop in stops {
+        if stop.position <= t && below.is_none_or(|b| stop.position >= b.position) {
+            below = Some(stop);
+        }
+
+        if stop.position >= t && above.is_none_or(|a| stop.position < a.position) {
+            above = Some(stop);
+        }
+    }
+
+    match (below, above) {
+        (Some(below), Some(above)) => {
+            let range = above.position - below.position;
+
+            if range <= 0.0 {
+                below.color
+            } else {
+                let t = (t - below.position) / range;
+                lerp_color(below.color, above.color, t)
+            }
+        }
+        (Some(stop), None) | (None, Some(stop)) => stop.color,
+        (None, None) => Color32::BLACK,
+    }
+}
+
+/// Interpolates the unmultiplied components, `Color32` stores them premultiplied by alpha.
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    let [a, b] = [a, b].map(|color| color.to_srgba_unmultiplied());
+
+    Color32::from_rgba_unmultiplied(
+        lerp(a[0], b[0]),
+        lerp(a[1], b[1]),
+        lerp(a[2], b[2]),
+        lerp(a[3], b[3]),
+    )
+}
+
+/// A user editable gradient with up to [`MAX_GRADIENT_STOPS`] stops.
+pub type Gradient = FixedList<GradientStop, MAX_GRADIENT_STOPS>;
+
+impl Gradient {
+    pub const DEFAULT: Self = Self::from_slice(&[
+        stop(0.0, 0x10103a),
+        stop(0.45, 0x2f7f8f),
+        stop(0.55, 0xd8c27a),
+        stop(1.0, 0xfff8e8),
+    ]);
+}
+
+pub fn gradient_editor(
+    gradient: &mut Gradient,
+) -> FixedListEditor<'_, GradientStop, MAX_GRADIENT_STOPS> {
+    FixedListEditor::new(gradient, |ui, stop| {
+        ui.add(
+            egui::DragValue::new(&mut stop.position)
+                .speed(0.005)
+                .range(0.0..=1.0),
+        ) | ui.color_edit_button_srgba(&mut stop.color)
+    })
+}
@@ -0,0 +1,259 @@
+//! Compares the edited settings (A) against a reference (B).
+
+use eframe::egui;
+
+use crate::{
+    render::{Cache, Renderer},
+    settings::Settings,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompareLayout {
+    SideBySide,
+    /// Shows B on top of A right of a draggable divider.
+    Wipe,
+}
+
+impl CompareLayout {
+    pub const VARIANTS: &'static [Self] = &[Self::SideBySide, Self::Wipe];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            CompareLayout::SideBySide => "Side by Side",
+            CompareLayout::Wipe => "Wipe",
+        }
+    }
+}
+
+/// The B side of the comparison, rendered independently of A.
+pub struct Reference {
+    pub settings: Settings,
+    pub texture: egui::TextureHandle,
+    pub cache: Option<Cache>,
+    pub changed: bool,
+    renderer: Renderer,
+}
+
+impl Reference {
+    fn new(ctx: &egui::Context, settings: Settings) -> Self {
+        Self {
+            settings,
+            texture: ctx.load_texture(
+                "reference",
+                egui::ColorImage::example(),
+                egui::TextureOptions::NEAREST,
+            ),
+            cache: None,
+            changed: true,
+            renderer: Default::default(),
+        }
+    }
+
+    /// Starts a render if the settings changed and shows it once it's done.
+    pub fn update(&mut self, ctx: &egui::Context) {
+        if self.changed {
+            self.changed = false;
+            self.renderer.start(&self.settings);
+        }
+
+        if let Some(render) = self.renderer.poll() {
+            self.cache = Some(render.upload(&mut self.texture));
+        }
+
+        if self.renderer.is_busy() {
+            // keep polling until the render is done
+            ctx.request_repaint();
+        }
+    }
+}
+
+pub struct Comparison {
+    enabled: bool,
+    layout: CompareLayout,
+    /// Pans and zooms B together with A.
+    link_view: bool,
+    /// Position of the wipe divider from the left, in `0.0..=1.0`.
+    divider: f32,
+    reference: Option<Reference>,
+}
+
+impl Default for Comparison {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            layout: CompareLayout::SideBySide,
+            link_view: true,
+            divider: 0.5,
+            reference: None,
+        }
+    }
+}
+
+impl Comparison {
+    /// Returns the reference while comparing.
+    pub fn active(&mut self) -> Option<&mut Reference> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.reference.as_mut()
+    }
+
+    /// Returns the reference if it should follow the view of A.
+    pub fn linked(&mut self) -> Option<&mut Reference> {
+        if !self.link_view {
+            return None;
+        }
+
+        self.active()
+    }
+
+    /// Shows the comparison controls above the preview.
+    ///
+    /// Returns `true` if A and B should be swapped, which needs access to A.
+    pub fn bar_ui(&mut self, ui: &mut egui::Ui, settings: &Settings) -> bool {
+        let mut swap = false;
+
+        ui.horizontal_wrapped(|ui| {
+            if ui.checkbox(&mut self.enabled, "Compare A / B").changed()
+                && self.enabled
+                && self.reference.is_none()
+            {
+                self.reference = Some(Reference::new(ui.ctx(), settings.clone()));
+            }
+
+            if !self.enabled {
+                return;
+            }
+
+            egui::ComboBox::from_id_salt("compare layout")
+                .selected_text(self.layout.to_str())
+                .show_ui(ui, |ui| {
+                    for &layout in CompareLayout::VARIANTS {
+                        ui.selectable_value(&mut self.layout, layout, layout.to_str());
+                    }
+                });
+
+            ui.checkbox(&mut self.link_view, "Link View")
+                .on_hover_text("Pan and zoom B together with A.");
+
+            swap = ui.button("Swap").clicked();
+            let copy = ui.button("Copy A to B").clicked();
+
+            if let Some(reference) = self.reference.as_mut().filter(|_| copy) {
+                reference.settings = settings.clone();
+                reference.changed = true;
+            }
+        });
+
+        if self.enabled {
+            ui.weak("The settings panel edits A.");
+        }
+
+        swap
+    }
+
+    /// Shows A and B, returning the area of both and the response of the A image.
+    ///
+    /// Returns `None` if not comparing.
+    pub fn images_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        texture: &egui::TextureHandle,
+        size: egui::Vec2,
+    ) -> Option<(egui::Rect, egui::Response)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let reference = self.reference.as_ref()?;
+
+        let image = |texture: &egui::TextureHandle, sense| {
+            egui::Image::new(egui::load::SizedTexture::new(texture.id(), size))
+                .fit_to_exact_size(size)
+                .sense(sense)
+        };
+
+        match self.layout {
+            CompareLayout::SideBySide => {
+                let inner = ui.horizontal(|ui| {
+                    let a = ui.add(image(texture, egui::Sense::click_and_drag()));
+                    let b = ui.add(image(&reference.texture, egui::Sense::hover()));
+
+                    corner_label(ui.painter(), a.rect.left_top(), egui::Align2::LEFT_TOP, "A");
+                    corner_label(ui.painter(), b.rect.left_top(), egui::Align2::LEFT_TOP, "B");
+
+                    a
+                });
+
+                Some((inner.response.rect, inner.inner))
+            }
+            CompareLayout::Wipe => {
+                let a = ui.add(image(texture, egui::Sense::click_and_drag()));
+                let rect = a.rect;
+                let x = rect.left() + rect.width() * self.divider;
+
+                ui.painter().image(
+                    reference.texture.id(),
+                    egui::Rect::from_min_max(egui::pos2(x, rect.top()), rect.max),
+                    egui::Rect::from_min_max(egui::pos2(self.divider, 0.0), egui::pos2(1.0, 1.0)),
+                    egui::Color32::WHITE,
+                );
+
+                let handle = ui.interact(
+                    egui::Rect::from_center_size(
+                        egui::pos2(x, rect.center().y),
+                        egui::vec2(8.0, rect.height()),
+                    ),
+                    ui.id().with("wipe divider"),
+                    egui::Sense::drag(),
+                );
+
+                if let Some(pos) = handle.interact_pointer_pos().filter(|_| handle.dragged()) {
+                    self.divider = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                }
+
+                if handle.hovered() || handle.dragged() {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                }
+
+                ui.painter().vline(
+                    x,
+                    rect.y_range(),
+                    egui::Stroke::new(2.0_f32, egui::Color32::WHITE),
+                );
+
+                corner_label(ui.painter(), rect.left_top(), egui::Align2::LEFT_TOP, "A");
+                corner_label(ui.painter(), rect.right_top(), egui::Align2::RIGHT_TOP, "B");
+
+                Some((rect, a))
+            }
+        }
+    }
+}
+
+fn corner_label(painter: &egui::Painter, corner: egui::Pos2, align: egui::Align2, text: &str) {
+    let galley = painter.layout_no_wrap(
+        text.into(),
+        egui::FontId::proportional(16.0),
+        egui::Color32::WHITE,
+    );
+
+    // keep some distance to the corner
+    let margin = egui::vec2(
+        if align.x() == egui::Align::Max {
+            -4.0
+        } else {
+            4.0
+        },
+        4.0,
+    );
+    let rect = align.anchor_size(corner + margin, galley.size() + egui::vec2(8.0, 4.0));
+
+    painter.rect_filled(rect, 3.0, egui::Color32::from_black_alpha(160));
+    painter.galley(
+        rect.center() - galley.size() * 0.5,
+        galley,
+        egui::Color32::WHITE,
+    );
+}
@@ -0,0 +1,113 @@
+//! Isolines of the sampled values, computed with marching squares.
+
+use eframe::egui;
+
+use crate::fixed_list::{FixedList, FixedListEditor};
+
+pub const MAX_THRESHOLDS: usize = 8;
+
+const CONTOUR_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 200, 0);
+
+/// A user editable list of up to [`MAX_THRESHOLDS`] noise values.
+pub type Thresholds = FixedList<f32, MAX_THRESHOLDS>;
+
+impl Thresholds {
+    pub const DEFAULT: Self = Self::from_slice(&[0.0]);
+}
+
+pub fn thresholds_editor(thresholds: &mut Thresholds) -> FixedListEditor<'_, f32, MAX_THRESHOLDS> {
+    FixedListEditor::new(thresholds, |ui, value| {
+        ui.add(egui::Slider::new(value, -1.0..=1.0).clamping(egui::SliderClamping::Never))
+    })
+}
+
+/// Returns the isolines of a `size * size` image as line segments in pixel coordinates.
+pub fn contours(values: &[f32], size: usize, thresholds: &Thresholds) -> Vec<[egui::Pos2; 2]> {
+    let mut segments = Vec::new();
+
+    for &threshold in thresholds.as_slice() {
+        marching_squares(values, size, threshold, &mut segments);
+    }
+
+    segments
+}
+
+/// Appends the isoline at `threshold` to `segments`.
+///
+/// The cells of the grid go from pixel center to pixel center.
+fn marching_squares(
+    values: &[f32],
+    size: usize,
+    threshold: f32,
+    segments: &mut Vec<[egui::Pos2; 2]>,
+) {
+    for y in 0..size.saturating_sub(1) {
+        for x in 0..size - 1 {
+            // clockwise from the top left
+            let corners = [[x, y], [x + 1, y], [x + 1, y + 1], [x, y + 1]];
+            let corner_values = corners.map(|[x, y]| values[y * size + x]);
+
+            if corner_values.iter().any(|value| !value.is_finite()) {
+                continue;
+            }
+
+            let above = corner_values.map(|value| value > threshold);
+
+            // edge `i` goes from corner `i` to the next one
+            let crossing = |edge: usize| {
+                let (a, b) = (edge, (edge + 1) % 4);
+                let t = (threshold - corner_values[a]) / (corner_values[b] - corner_values[a]);
+                let [ax, ay] = corners[a].map(|c| c as f32 + 0.5);
+                let [bx, by] = corners[b].map(|c| c as f32 + 0.5);
+                egui::pos2(ax + (bx - ax) * t, ay + (by - ay) * t)
+            };
+
+            let mut crossed = [0; 4];
+            let mut count = 0;
+
+            for edge in 0..4 {
+                if above[edge] != above[(edge + 1) % 4] {
+                    crossed[count] = edge;
+                    count += 1;
+                }
+            }
+
+            match count {
+                2 => segments.push([crossing(crossed[0]), crossing(crossed[1])]),
+                4 => {
+                    // a saddle, decide by the center which diagonal is connected
+                    let center = corner_values.iter().sum::<f32>() * 0.25;
+
+                    if (center > threshold) == above[0] {
+                        segments.push([crossing(0), crossing(1)]);
+                        segments.push([crossing(2), crossing(3)]);
+                    } else {
+                        segments.push([crossing(3), crossing(0)]);
+                        segments.push([crossing(1), crossing(2)]);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Draws the `segments` of a `size * size` image displayed in `rect`.
+pub fn paint(painter: &egui::Painter, rect: egui::Rect, size: usize, segments: &[[egui::Pos2; 2]]) {
+    if size == 0 {
+        return;
+    }
+
+    let pixel_size = rect.width() / size as f32;
+    let stroke = egui::Stroke::new(1.5_f32, CONTOUR_COLOR);
+
+    painter.extend(segments.iter().map(|&[a, b]| {
+        egui::Shape::line_segment(
+            [
+                rect.min + a.to_vec2() * pixel_size,
+                rect.min + b.to_vec2() * pixel_size,
+            ],
+            stroke,
+        )
+    }));
+}
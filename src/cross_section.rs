@@ -0,0 +1,211 @@
+//! Plots the noise along a line across the preview.
+
+use eframe::egui;
+
+use crate::{sampling, settings::Settings};
+
+/// Number of samples along the line, independent of the texture size so the plot
+/// shows detail between pixels.
+const SAMPLES: usize = 512;
+
+const LINE_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 220, 255);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CrossSectionMode {
+    #[default]
+    Off,
+    /// The row under the cursor.
+    Row,
+    /// The column under the cursor.
+    Column,
+    /// A line drawn by dragging with the secondary mouse button.
+    Line,
+}
+
+impl CrossSectionMode {
+    pub const VARIANTS: &'static [Self] = &[Self::Off, Self::Row, Self::Column, Self::Line];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            CrossSectionMode::Off => "Off",
+            CrossSectionMode::Row => "Row",
+            CrossSectionMode::Column => "Column",
+            CrossSectionMode::Line => "Line",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CrossSection {
+    mode: CrossSectionMode,
+    /// Last hovered position in pixel coordinates.
+    hovered: Option<egui::Pos2>,
+    /// The drawn line in pixel coordinates.
+    line: Option<[egui::Pos2; 2]>,
+}
+
+impl CrossSection {
+    /// Tracks the hovered pixel and lets the line be drawn on a preview image.
+    pub fn interact(&mut self, response: &egui::Response, size: usize) {
+        if self.mode == CrossSectionMode::Off || size == 0 {
+            return;
+        }
+
+        let to_pixel = |pos: egui::Pos2| {
+            let local = (pos - response.rect.min) / response.rect.size() * size as f32;
+            local
+                .to_pos2()
+                .clamp(egui::Pos2::ZERO, egui::pos2(size as f32, size as f32))
+        };
+
+        if let Some(pos) = response.hover_pos() {
+            self.hovered = Some(to_pixel(pos));
+        }
+
+        if self.mode != CrossSectionMode::Line {
+            return;
+        }
+
+        let Some(pos) = response.interact_pointer_pos() else {
+            return;
+        };
+
+        if response.drag_started_by(egui::PointerButton::Secondary) {
+            self.line = Some([to_pixel(pos); 2]);
+        } else if response.dragged_by(egui::PointerButton::Secondary) {
+            self.line = self.line.map(|[start, _]| [start, to_pixel(pos)]);
+        }
+    }
+
+    /// Returns the start and end of the plotted line in pixel coordinates, the image spanning
+    /// `0..size`.
+    fn segment(&self, size: usize) -> Option<[egui::Pos2; 2]> {
+        let size = size as f32;
+
+        match self.mode {
+            CrossSectionMode::Off => None,
+            CrossSectionMode::Row => {
+                // go through the middle of the pixel
+                let y = self.hovered?.y.floor().min(size - 1.0) + 0.5;
+                Some([egui::pos2(0.0, y), egui::pos2(size, y)])
+            }
+            CrossSectionMode::Column => {
+                let x = self.hovered?.x.floor().min(size - 1.0) + 0.5;
+                Some([egui::pos2(x, 0.0), egui::pos2(x, size)])
+            }
+            CrossSectionMode::Line => self.line.filter(|[start, end]| start != end),
+        }
+    }
+
+    /// Draws the plotted line onto a preview image.
+    pub fn paint(&self, painter: &egui::Painter, rect: egui::Rect, size: usize) {
+        let Some([start, end]) = self.segment(size) else {
+            return;
+        };
+
+        let pixel_size = rect.width() / size as f32;
+        let to_screen = |pos: egui::Pos2| rect.min + pos.to_vec2() * pixel_size;
+
+        painter.line_segment(
+            [to_screen(start), to_screen(end)],
+            egui::Stroke::new(1.5_f32, LINE_COLOR),
+        );
+    }
+
+    /// Shows the mode selection and the plot.
+    ///
+    /// `settings` are the ones of the render shown in the preview.
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings, width: f32) {
+        ui.horizontal(|ui| {
+            ui.label("Cross Section");
+
+            egui::ComboBox::from_id_salt("cross section mode")
+                .selected_text(self.mode.to_str())
+                .show_ui(ui, |ui| {
+                    for &mode in CrossSectionMode::VARIANTS {
+                        ui.selectable_value(&mut self.mode, mode, mode.to_str());
+                    }
+                });
+        });
+
+        if self.mode == CrossSectionMode::Off {
+            return;
+        }
+
+        let size = settings.texture_size;
+
+        let Some([start, end]) = self.segment(size) else {
+            ui.label(match self.mode {
+                CrossSectionMode::Line => "Drag over the preview with the right mouse button.",
+                _ => "Hover over the preview.",
+            });
+            return;
+        };
+
+        let Some(sampler) = sampling::point_sampler(settings, size) else {
+            return;
+        };
+
+        let values = (0..SAMPLES)
+            .map(|i| {
+                let pos = start.lerp(end, i as f32 / (SAMPLES - 1) as f32);
+
+                // pixels are sampled at their top left corner
+                sampler([pos.x - 0.5, pos.y - 0.5])
+            })
+            .collect::<Vec<_>>();
+
+        plot(ui, &values, width);
+    }
+}
+
+fn plot(ui: &mut egui::Ui, values: &[f32], width: f32) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, 120.0), egui::Sense::hover());
+
+    let rect = response.rect;
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let lo = finite.clone().fold(-1.0, f32::min);
+    let hi = finite.fold(1.0, f32::max);
+
+    let x_of = |i: usize| rect.left() + i as f32 / (values.len() - 1) as f32 * rect.width();
+    let y_of = |value: f32| rect.bottom() - (value - lo) / (hi - lo) * rect.height();
+
+    for mark in [-1.0, 0.0, 1.0] {
+        painter.hline(
+            rect.x_range(),
+            y_of(mark),
+            egui::Stroke::new(1.0_f32, visuals.weak_text_color()),
+        );
+    }
+
+    // break the line at NaN and infinite values
+    let stroke = egui::Stroke::new(1.5_f32, LINE_COLOR);
+    let mut points = Vec::new();
+
+    for (i, &value) in values.iter().enumerate() {
+        if value.is_finite() {
+            points.push(egui::pos2(x_of(i), y_of(value)));
+        } else {
+            painter.add(egui::Shape::line(std::mem::take(&mut points), stroke));
+        }
+    }
+
+    painter.add(egui::Shape::line(points, stroke));
+
+    if let Some(pos) = response.hover_pos() {
+        let i = (((pos.x - rect.left()) / rect.width() * (values.len() - 1) as f32).round()
+            as usize)
+            .min(values.len() - 1);
+
+        painter.vline(
+            x_of(i),
+            rect.y_range(),
+            egui::Stroke::new(1.0_f32, visuals.text_color()),
+        );
+
+        response.on_hover_text_at_pointer(format!("{:?}", values[i]));
+    }
+}
@@ -0,0 +1,594 @@
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use eframe::egui;
+
+use crate::{
+    animation,
+    render::{Render, Renderer},
+    sampling,
+    settings::{Dimension, Settings},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_EXPORT_SIZE: usize = 8192;
+
+/// An export holds the values, the pixels and the encoded bytes at once, which at 8192² is more
+/// than a browser tab can allocate.
+#[cfg(target_arch = "wasm32")]
+pub const MAX_EXPORT_SIZE: usize = 4096;
+
+/// Renders the noise at `size * size` pixels, independent of the texture size.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_png(settings: &Settings, size: usize) -> Result<Vec<u8>, String> {
+    let pixels = render_pixels(settings, size)?;
+    encode_png(size, size, &pixels).map_err(|e| format!("failed to encode png: {e}"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn render_pixels(settings: &Settings, size: usize) -> Result<Vec<egui::Color32>, String> {
+    let mut values = vec![0.0; size * size];
+
+    if !sampling::sample(settings, size, &mut values) {
+        return Err(sampling::UNSUPPORTED.into());
+    }
+
+    let mut pixels = vec![egui::Color32::BLACK; size * size];
+    sampling::colorize(settings, size, &values, &mut pixels);
+    Ok(pixels)
+}
+
+/// How z and w change over the frames of an exported animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationPath {
+    /// Moves z by `step` each frame.
+    Z { step: f32 },
+    /// Moves w by `step` each frame.
+    W { step: f32 },
+    /// Moves z and w in a circle around the current slice so the last frame
+    /// leads seamlessly into the first one. Samples 4D noise.
+    Circle { radius: f32 },
+}
+
+impl AnimationPath {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            AnimationPath::Z { .. } => "Z",
+            AnimationPath::W { .. } => "W",
+            AnimationPath::Circle { .. } => "Circle in Z / W",
+        }
+    }
+
+    /// Returns the settings of the `frame`th of `frames` frames.
+    pub fn frame_settings(
+        self,
+        settings: &Settings,
+        frame: usize,
+        frames: usize,
+    ) -> Result<Settings, String> {
+        let mut settings = settings.clone();
+        let frame = frame as f32;
+
+        match self {
+            AnimationPath::Z { step } => {
+                if settings.dimension == Dimension::D2 {
+                    return Err("animating z requires 3D or 4D noise".into());
+                }
+
+                settings.z += step * frame;
+            }
+            AnimationPath::W { step } => {
+                if settings.dimension != Dimension::D4 {
+                    return Err("animating w requires 4D noise".into());
+                }
+
+                settings.w += step * frame;
+            }
+            AnimationPath::Circle { radius } => {
+                if settings.dimension == Dimension::D2 {
+                    settings.z = 0.0;
+                }
+
+                if settings.dimension != Dimension::D4 {
+                    settings.w = 0.0;
+                }
+
+                settings.dimension = Dimension::D4;
+                [settings.z, settings.w] = animation::circle_zw(
+                    [settings.z, settings.w],
+                    radius,
+                    frame / frames.max(1) as f32,
+                );
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Inserts the frame number in front of the file extension.
+#[cfg(not(target_arch = "wasm32"))]
+fn numbered_path(path: &Path, frame: usize) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_owned();
+    name.push(format!("_{frame:04}"));
+
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+
+    path.with_file_name(name)
+}
+
+pub fn encode_png(
+    width: usize,
+    height: usize,
+    pixels: &[egui::Color32],
+) -> Result<Vec<u8>, png::EncodingError> {
+    let mut bytes = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&rgba_bytes(pixels))?;
+    writer.finish()?;
+
+    Ok(bytes)
+}
+
+fn rgba_bytes(pixels: &[egui::Color32]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect()
+}
+
+/// Writes the file to `path`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(path: &Path, _mime: &str, bytes: &[u8]) -> Result<(), String> {
+    std::fs::write(path, bytes)
+        .map_err(|e| format!("failed to write {:?}: {e}", path.display().to_string()))
+}
+
+/// Makes the browser download the file under the file name of `path`.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(path: &Path, mime: &str, bytes: &[u8]) -> Result<(), String> {
+    use eframe::wasm_bindgen::{JsCast as _, JsValue};
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    let js_error = |e: JsValue| format!("failed to download {file_name:?}: {e:?}");
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(js_error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+
+    let anchor = document
+        .create_element("a")
+        .map_err(js_error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "failed to create anchor element")?;
+
+    anchor.set_href(&url);
+    anchor.set_download(&file_name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url).map_err(js_error)
+}
+
+pub fn status_ui(ui: &mut egui::Ui, status: &Option<Result<String, String>>) {
+    match status {
+        Some(Ok(message)) => {
+            ui.label(message);
+        }
+        Some(Err(error)) => {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        None => (),
+    }
+}
+
+/// Shows the save dialog. Returns `None` if it was canceled.
+#[cfg(not(target_arch = "wasm32"))]
+fn pick_path(file_name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .set_file_name(file_name)
+        .add_filter("PNG", &["png"])
+        .save_file()
+}
+
+/// The file is downloaded on the web, so there is nothing to pick.
+#[cfg(target_arch = "wasm32")]
+fn pick_path(file_name: &str) -> Option<PathBuf> {
+    Some(file_name.into())
+}
+
+/// Collects the bytes of an animated png, the encoder keeps its writer until it's finished.
+#[derive(Clone, Default)]
+struct SharedBytes(Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for SharedBytes {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+enum Output {
+    Png,
+    Apng {
+        writer: png::Writer<SharedBytes>,
+        bytes: SharedBytes,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    PngSequence,
+}
+
+/// Renders the images of an export one after another with a [`Renderer`] and writes them.
+struct Job {
+    renderer: Renderer,
+    images: Vec<Settings>,
+    /// Number of images that are written.
+    done: usize,
+    output: Output,
+    path: PathBuf,
+}
+
+impl Job {
+    fn start(images: Vec<Settings>, output: Output, path: PathBuf) -> Self {
+        let mut renderer = Renderer::without_stats();
+        renderer.start(&images[0]);
+
+        Self {
+            renderer,
+            images,
+            done: 0,
+            output,
+            path,
+        }
+    }
+
+    /// Returns the progress of the whole export in `0.0..=1.0`.
+    fn progress(&self) -> f32 {
+        (self.done as f32 + self.renderer.progress().unwrap_or(0.0)) / self.images.len() as f32
+    }
+
+    /// Writes the image if it finished since the last call. Returns the status once the export
+    /// is done or failed.
+    fn poll(&mut self) -> Option<Result<String, String>> {
+        let render = self.renderer.poll()?;
+        self.write(render).transpose()
+    }
+
+    fn write(&mut self, render: Render) -> Result<Option<String>, String> {
+        if !render.success {
+            return Err(sampling::UNSUPPORTED.into());
+        }
+
+        let encoding_error = |e: png::EncodingError| format!("failed to encode png: {e}");
+
+        match &mut self.output {
+            Output::Png => {
+                let bytes =
+                    encode_png(render.size, render.size, &render.pixels).map_err(encoding_error)?;
+                save_file(&self.path, "image/png", &bytes)?;
+            }
+            Output::Apng { writer, .. } => {
+                writer
+                    .write_image_data(&rgba_bytes(&render.pixels))
+                    .map_err(encoding_error)?;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Output::PngSequence => {
+                let bytes =
+                    encode_png(render.size, render.size, &render.pixels).map_err(encoding_error)?;
+                save_file(&numbered_path(&self.path, self.done), "image/png", &bytes)?;
+            }
+        }
+
+        self.done += 1;
+
+        if let Some(settings) = self.images.get(self.done) {
+            self.renderer.start(settings);
+            return Ok(None);
+        }
+
+        let path = |path: &Path| format!("{:?}", path.display().to_string());
+
+        let message = match std::mem::replace(&mut self.output, Output::Png) {
+            Output::Png => format!("exported {}", path(&self.path)),
+            Output::Apng { writer, bytes } => {
+                writer.finish().map_err(encoding_error)?;
+                save_file(&self.path, "image/apng", &bytes.0.borrow())?;
+                format!("exported {}", path(&self.path))
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Output::PngSequence => format!(
+                "exported {} frames from {} to {}",
+                self.done,
+                path(&numbered_path(&self.path, 0)),
+                path(&numbered_path(&self.path, self.done - 1)),
+            ),
+        };
+
+        Ok(Some(message))
+    }
+}
+
+/// Writes the finished images of the export. Updates `status` once it's done.
+fn poll_job(
+    ctx: &egui::Context,
+    job: &mut Option<Job>,
+    status: &mut Option<Result<String, String>>,
+) {
+    let Some(running) = job else {
+        return;
+    };
+
+    if let Some(result) = running.poll() {
+        *status = Some(result);
+        *job = None;
+        return;
+    }
+
+    // keep polling until the export is done, also while the section is collapsed
+    ctx.request_repaint();
+}
+
+/// Shows the progress of the export and a button to cancel it.
+fn progress_ui(ui: &mut egui::Ui, job: &mut Option<Job>) {
+    let Some(running) = job else {
+        return;
+    };
+
+    let progress = running.progress();
+
+    ui.horizontal(|ui| {
+        ui.add(egui::ProgressBar::new(progress).show_percentage());
+
+        if ui.button("Cancel").clicked() {
+            *job = None;
+        }
+    });
+}
+
+/// Returns the settings to render an export at `size * size` pixels.
+fn export_settings(settings: &Settings, size: usize) -> Settings {
+    Settings {
+        texture_size: size,
+        // the isolines are drawn by the ui and aren't part of the image
+        contours: false,
+        ..settings.clone()
+    }
+}
+
+/// Ui state of the "Export PNG" section.
+pub struct PngExport {
+    size: usize,
+    job: Option<Job>,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for PngExport {
+    fn default() -> Self {
+        Self {
+            size: 1024,
+            job: None,
+            status: None,
+        }
+    }
+}
+
+impl PngExport {
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        poll_job(ui.ctx(), &mut self.job, &mut self.status);
+
+        egui::CollapsingHeader::new("Export PNG").show(ui, |ui| {
+            egui::Grid::new("export png").num_columns(2).show(ui, |ui| {
+                ui.add(egui::Label::new("Size").selectable(false));
+                ui.add(egui::DragValue::new(&mut self.size).range(1..=MAX_EXPORT_SIZE));
+                ui.end_row();
+            });
+
+            if ui
+                .add_enabled(self.job.is_none(), egui::Button::new("Export PNG…"))
+                .clicked()
+                && let Some(path) = pick_path("noise.png")
+            {
+                let images = vec![export_settings(settings, self.size)];
+                self.job = Some(Job::start(images, Output::Png, path));
+                self.status = None;
+            }
+
+            progress_ui(ui, &mut self.job);
+            status_ui(ui, &self.status);
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnimationFormat {
+    Apng,
+    #[cfg(not(target_arch = "wasm32"))]
+    PngSequence,
+}
+
+impl AnimationFormat {
+    const VARIANTS: &'static [Self] = &[
+        Self::Apng,
+        #[cfg(not(target_arch = "wasm32"))]
+        Self::PngSequence,
+    ];
+
+    fn to_str(self) -> &'static str {
+        match self {
+            AnimationFormat::Apng => "Animated PNG",
+            #[cfg(not(target_arch = "wasm32"))]
+            AnimationFormat::PngSequence => "PNG Sequence",
+        }
+    }
+}
+
+/// Ui state of the "Export Animation" section.
+pub struct AnimationExport {
+    size: usize,
+    frames: usize,
+    fps: u16,
+    format: AnimationFormat,
+    path: AnimationPath,
+    job: Option<Job>,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for AnimationExport {
+    fn default() -> Self {
+        Self {
+            size: 256,
+            frames: 60,
+            fps: 30,
+            format: AnimationFormat::Apng,
+            path: AnimationPath::Circle { radius: 0.5 },
+            job: None,
+            status: None,
+        }
+    }
+}
+
+impl AnimationExport {
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        poll_job(ui.ctx(), &mut self.job, &mut self.status);
+
+        egui::CollapsingHeader::new("Export Animation").show(ui, |ui| {
+            egui::Grid::new("export animation")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.add(egui::Label::new("Format").selectable(false));
+                    egui::ComboBox::from_id_salt("animation format")
+                        .selected_text(self.format.to_str())
+                        .show_ui(ui, |ui| {
+                            for &format in AnimationFormat::VARIANTS {
+                                ui.selectable_value(&mut self.format, format, format.to_str());
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.add(egui::Label::new("Size").selectable(false));
+                    ui.add(egui::DragValue::new(&mut self.size).range(1..=MAX_EXPORT_SIZE));
+                    ui.end_row();
+
+                    ui.add(egui::Label::new("Frames").selectable(false));
+                    ui.add(egui::DragValue::new(&mut self.frames).range(1..=1000));
+                    ui.end_row();
+
+                    if self.format == AnimationFormat::Apng {
+                        ui.add(egui::Label::new("Frames per Second").selectable(false));
+                        ui.add(egui::DragValue::new(&mut self.fps).range(1..=100));
+                        ui.end_row();
+                    }
+
+                    ui.add(egui::Label::new("Path").selectable(false));
+                    egui::ComboBox::from_id_salt("animation path")
+                        .selected_text(self.path.to_str())
+                        .show_ui(ui, |ui| {
+                            for path in [
+                                AnimationPath::Z { step: 0.01 },
+                                AnimationPath::W { step: 0.01 },
+                                AnimationPath::Circle { radius: 0.5 },
+                            ] {
+                                let selected = self.path.to_str() == path.to_str();
+
+                                if ui.selectable_label(selected, path.to_str()).clicked()
+                                    && !selected
+                                {
+                                    self.path = path;
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    match &mut self.path {
+                        AnimationPath::Z { step } | AnimationPath::W { step } => {
+                            ui.add(egui::Label::new("Step per Frame").selectable(false));
+                            ui.add(egui::DragValue::new(step).speed(0.001));
+                        }
+                        AnimationPath::Circle { radius } => {
+                            ui.add(egui::Label::new("Radius").selectable(false));
+                            ui.add(
+                                egui::DragValue::new(radius)
+                                    .speed(0.01)
+                                    .range(0.0..=f32::MAX),
+                            );
+                        }
+                    }
+                    ui.end_row();
+                });
+
+            if ui
+                .add_enabled(self.job.is_none(), egui::Button::new("Export Animation…"))
+                .clicked()
+            {
+                self.status = self.start(settings).err().map(Err);
+            }
+
+            progress_ui(ui, &mut self.job);
+            status_ui(ui, &self.status);
+        });
+    }
+
+    /// Asks where to save the animation and starts rendering the frames.
+    fn start(&mut self, settings: &Settings) -> Result<(), String> {
+        let images = (0..self.frames)
+            .map(|frame| {
+                self.path
+                    .frame_settings(settings, frame, self.frames)
+                    .map(|settings| export_settings(&settings, self.size))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let output = match self.format {
+            AnimationFormat::Apng => {
+                let encoding_error = |e: png::EncodingError| format!("failed to encode png: {e}");
+                let bytes = SharedBytes::default();
+
+                let mut encoder =
+                    png::Encoder::new(bytes.clone(), self.size as u32, self.size as u32);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder
+                    .set_animated(self.frames as u32, 0)
+                    .map_err(encoding_error)?;
+                encoder
+                    .set_frame_delay(1, self.fps)
+                    .map_err(encoding_error)?;
+
+                let writer = encoder.write_header().map_err(encoding_error)?;
+                Output::Apng { writer, bytes }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            AnimationFormat::PngSequence => Output::PngSequence,
+        };
+
+        if let Some(path) = pick_path("noise.png") {
+            self.job = Some(Job::start(images, output, path));
+        }
+
+        Ok(())
+    }
+}
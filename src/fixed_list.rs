@@ -0,0 +1,128 @@
+//! A small list setting and its editor, used for the gradient stops and the thresholds.
+
+use eframe::egui;
+
+/// A user editable list of at least one and at most `N` items.
+///
+/// This is a fixed size array so the default settings can stay a `const`.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedList<T, const N: usize> {
+    items: [T; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> FixedList<T, N> {
+    pub const fn from_slice(slice: &[T]) -> Self {
+        assert!(!slice.is_empty() && slice.len() <= N);
+
+        // the unused items are never read
+        let mut items = [slice[0]; N];
+        let mut i = 0;
+
+        while i < slice.len() {
+            items[i] = slice[i];
+            i += 1;
+        }
+
+        Self {
+            items,
+            len: slice.len(),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.items[..self.len]
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn push(&mut self, item: T) {
+        if !self.is_full() {
+            self.items[self.len] = item;
+            self.len += 1;
+        }
+    }
+
+    /// Removes the item at `index` unless it's the last one left.
+    pub fn remove(&mut self, index: usize) {
+        if self.len > 1 {
+            self.items[index..self.len].rotate_left(1);
+            self.len -= 1;
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for FixedList<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items[..self.len] == other.items[..other.len]
+    }
+}
+
+/// Shows each item in a row with a delete button, and a button that appends a copy of the
+/// last item.
+pub struct FixedListEditor<'v, T, const N: usize> {
+    list: &'v mut FixedList<T, N>,
+    item: fn(&mut egui::Ui, &mut T) -> egui::Response,
+}
+
+impl<'v, T, const N: usize> FixedListEditor<'v, T, N> {
+    pub fn new(
+        list: &'v mut FixedList<T, N>,
+        item: fn(&mut egui::Ui, &mut T) -> egui::Response,
+    ) -> Self {
+        Self { list, item }
+    }
+}
+
+impl<T: Copy, const N: usize> egui::Widget for FixedListEditor<'_, T, N> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let Self { list, item } = self;
+
+        let egui::InnerResponse {
+            inner,
+            mut response,
+        } = ui.vertical(|ui| {
+            let mut changed = false;
+            let mut remove = None;
+            let can_remove = list.as_slice().len() > 1;
+
+            for (i, value) in list.as_mut_slice().iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    changed |= item(ui, value).changed();
+
+                    if ui.add_enabled(can_remove, egui::Button::new("🗑")).clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+
+            if let Some(i) = remove {
+                list.remove(i);
+                changed = true;
+            }
+
+            if ui
+                .add_enabled(!list.is_full(), egui::Button::new("+"))
+                .clicked()
+            {
+                let last = *list.as_slice().last().expect("the list is never empty");
+                list.push(last);
+                changed = true;
+            }
+
+            changed
+        });
+
+        if inner {
+            response.mark_changed();
+        }
+
+        response
+    }
+}
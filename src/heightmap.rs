@@ -0,0 +1,194 @@
+//! Shows the sampled values as a shaded heightfield.
+//!
+//! The mesh is projected and depth sorted on the cpu and drawn with the egui
+//! painter, so it works with any rendering backend.
+
+use std::f32::consts::FRAC_PI_2;
+
+use eframe::egui;
+
+use crate::{
+    colormap, preview,
+    settings::Settings,
+    shading::{self, Vec3, cross, dot, normalize, sub},
+};
+
+/// Maximum number of vertices along each side of the mesh.
+const MAX_GRID_SIZE: usize = 128;
+
+/// How much the camera rotates per dragged point.
+const ORBIT_SPEED: f32 = 0.01;
+
+/// The orbit camera.
+pub struct HeightmapView {
+    /// Rotation around the vertical axis.
+    yaw: f32,
+    /// Angle above the horizon, a pitch of 90° looks straight down.
+    pitch: f32,
+    zoom: f32,
+}
+
+impl Default for HeightmapView {
+    fn default() -> Self {
+        Self {
+            yaw: 0.6,
+            pitch: 0.7,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl HeightmapView {
+    /// Shows the `values` rendered with `settings`. Dragging orbits the camera, scrolling zooms.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        settings: &Settings,
+        values: &[f32],
+        height_scale: f32,
+        desired_size: egui::Vec2,
+    ) -> egui::Response {
+        let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::drag());
+
+        if response.dragged() {
+            response.ctx.set_cursor_icon(egui::CursorIcon::Grabbing);
+
+            let delta = response.drag_delta();
+            self.yaw -= delta.x * ORBIT_SPEED;
+            self.pitch = (self.pitch + delta.y * ORBIT_SPEED).clamp(0.05, FRAC_PI_2);
+        } else if response.hovered() {
+            response.ctx.set_cursor_icon(egui::CursorIcon::Grab);
+            self.zoom = (self.zoom * preview::scroll_zoom(&response)).clamp(0.2, 10.0);
+        }
+
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        painter.add(self.mesh(rect, settings, values, height_scale));
+
+        response
+    }
+
+    /// Projects a point of the heightfield, returning its screen position and its distance
+    /// towards the camera.
+    fn project(&self, rect: egui::Rect, [x, y, z]: Vec3) -> (egui::Pos2, f32) {
+        let (yaw_sin, yaw_cos) = self.yaw.sin_cos();
+        let (pitch_sin, pitch_cos) = self.pitch.sin_cos();
+
+        let x_rotated = x * yaw_cos - z * yaw_sin;
+        let z_rotated = x * yaw_sin + z * yaw_cos;
+
+        let up = y * pitch_cos - z_rotated * pitch_sin;
+        let depth = z_rotated * pitch_cos + y * pitch_sin;
+
+        let scale = rect.width().min(rect.height()) * 0.35 * self.zoom;
+        let pos = rect.center() + egui::vec2(x_rotated, -up) * scale;
+
+        (pos, depth)
+    }
+
+    fn mesh(
+        &self,
+        rect: egui::Rect,
+        settings: &Settings,
+        values: &[f32],
+        height_scale: f32,
+    ) -> egui::Mesh {
+        let size = settings.texture_size;
+        let mut mesh = egui::Mesh::default();
+
+        if size < 2 || values.len() < size * size {
+            return mesh;
+        }
+
+        // the sampled rows and columns, including the last one
+        let step = size.div_ceil(MAX_GRID_SIZE);
+        let mut samples = (0..size).step_by(step).collect::<Vec<_>>();
+
+        if samples.last() != Some(&(size - 1)) {
+            samples.push(size - 1);
+        }
+
+        let n = samples.len();
+        let lut = settings.colormap.lut(&settings.gradient);
+
+        let value_at = |column: usize, row: usize| values[samples[row] * size + samples[column]];
+
+        let point_at = |column: usize, row: usize| -> Vec3 {
+            let value = value_at(column, row);
+
+            // values outside of -1..=1 saturate like they do in the colormap
+            let height = if value.is_finite() {
+                value.clamp(-1.0, 1.0)
+            } else {
+                0.0
+            };
+
+            [
+                samples[column] as f32 / (size - 1) as f32 * 2.0 - 1.0,
+                height * height_scale * 0.5,
+                samples[row] as f32 / (size - 1) as f32 * 2.0 - 1.0,
+            ]
+        };
+
+        let points = (0..n * n)
+            .map(|i| point_at(i % n, i / n))
+            .collect::<Vec<_>>();
+
+        let projected = points
+            .iter()
+            .map(|&point| self.project(rect, point))
+            .collect::<Vec<_>>();
+
+        // painter's algorithm, draw the quads furthest from the camera first
+        let mut quads = Vec::with_capacity((n - 1) * (n - 1));
+
+        for row in 0..n - 1 {
+            for column in 0..n - 1 {
+                let corners = quad_corners(n, column, row);
+                let depth = corners.iter().map(|&i| projected[i].1).sum::<f32>();
+                quads.push((depth, column, row));
+            }
+        }
+
+        quads.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let light = shading::light_direction(settings);
+
+        for (_, column, row) in quads {
+            let corners = quad_corners(n, column, row);
+            let [top_left, top_right, _, bottom_left] = corners.map(|i| points[i]);
+
+            let mut normal = normalize(cross(sub(bottom_left, top_left), sub(top_right, top_left)));
+
+            if normal[1] < 0.0 {
+                normal = normal.map(|c| -c);
+            }
+
+            let shade = 0.35 + 0.65 * dot(normal, light).max(0.0);
+
+            let value = corners.iter().map(|&i| value_at(i % n, i / n)).sum::<f32>() * 0.25;
+
+            let [r, g, b, _] = lut[colormap::lut_index(value)]
+                .to_array()
+                .map(|c| (c as f32 * shade) as u8);
+            let color = egui::Color32::from_rgb(r, g, b);
+
+            let first = mesh.vertices.len() as u32;
+
+            for i in corners {
+                mesh.colored_vertex(projected[i].0, color);
+            }
+
+            mesh.add_triangle(first, first + 1, first + 2);
+            mesh.add_triangle(first, first + 2, first + 3);
+        }
+
+        mesh
+    }
+}
+
+/// Returns the point indices of a quad, going around clockwise from the top left.
+fn quad_corners(n: usize, column: usize, row: usize) -> [usize; 4] {
+    let i = row * n + column;
+    [i, i + 1, i + n + 1, i + n]
+}
@@ -0,0 +1,236 @@
+//! Undo and redo of settings changes.
+
+use std::collections::VecDeque;
+
+use eframe::egui;
+
+use crate::settings::Settings;
+
+/// Number of kept snapshots, older ones are dropped.
+const MAX_HISTORY: usize = 100;
+
+/// Number of changed fields named in an entry of the history list.
+const MAX_LABEL_FIELDS: usize = 3;
+
+/// Seconds after the last change in which further changes of the same fields are merged into
+/// its entry. Scroll zooming changes the settings each frame without holding a button.
+const MERGE_WINDOW: f64 = 0.5;
+
+const UNDO: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+const REDO: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
+
+#[derive(Default)]
+pub struct History {
+    entries: VecDeque<Entry>,
+    /// Index of the entry that matches the current settings.
+    current: usize,
+}
+
+struct Entry {
+    settings: Settings,
+    /// The settings as key value pairs, to detect and describe changes.
+    fields: Vec<(&'static str, String)>,
+    /// The keys that differ from the previous entry.
+    changed: Vec<&'static str>,
+    /// Time of the last change of this entry, see [`egui::InputState::time`].
+    time: f64,
+}
+
+impl History {
+    /// Adds a snapshot if the settings changed since the current entry.
+    ///
+    /// Nothing is recorded while a pointer button is held so a drag becomes a single entry, and
+    /// quick successive changes of the same fields are merged, like the steps of a scroll zoom.
+    pub fn record(&mut self, ctx: &egui::Context, settings: &Settings) {
+        if ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
+
+        let fields = settings.fields();
+        let time = ctx.input(|i| i.time);
+
+        let changed = match self.entries.get(self.current) {
+            Some(entry) if entry.fields == fields => return,
+            Some(entry) => changed_keys(&entry.fields, &fields),
+            None => Vec::new(),
+        };
+
+        let is_last = self.current + 1 == self.entries.len();
+
+        if let Some(entry) = self.entries.get_mut(self.current)
+            && self.current > 0
+            && is_last
+            && time - entry.time < MERGE_WINDOW
+            && changed.iter().all(|key| entry.changed.contains(key))
+        {
+            entry.settings = settings.clone();
+            entry.fields = fields;
+            entry.time = time;
+            return;
+        }
+
+        // a new change discards the undone entries
+        self.entries.truncate(self.current + 1);
+
+        self.entries.push_back(Entry {
+            settings: settings.clone(),
+            fields,
+            changed,
+            time,
+        });
+
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.pop_front();
+        }
+
+        self.current = self.entries.len() - 1;
+    }
+
+    fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    fn can_redo(&self) -> bool {
+        self.current + 1 < self.entries.len()
+    }
+
+    fn jump(&mut self, index: usize) -> Settings {
+        self.current = index;
+        self.entries[index].settings.clone()
+    }
+
+    /// Handles the undo and redo shortcuts. Returns the settings to restore.
+    pub fn shortcuts(&mut self, ctx: &egui::Context) -> Option<Settings> {
+        // text fields have their own undo
+        if ctx.wants_keyboard_input() {
+            return None;
+        }
+
+        // redo first, undo would also match with shift held
+        if ctx.input_mut(|i| i.consume_shortcut(&REDO)) {
+            return self.can_redo().then(|| self.jump(self.current + 1));
+        }
+
+        if ctx.input_mut(|i| i.consume_shortcut(&UNDO)) && self.can_undo() {
+            return Some(self.jump(self.current - 1));
+        }
+
+        None
+    }
+
+    /// Shows the undo and redo buttons and the history list. Returns the settings to restore.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<Settings> {
+        let mut restore = None;
+
+        ui.horizontal(|ui| {
+            let undo = ui
+                .add_enabled(self.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text(ui.ctx().format_shortcut(&UNDO));
+
+            if undo.clicked() {
+                restore = Some(self.jump(self.current - 1));
+            }
+
+            let redo = ui
+                .add_enabled(self.can_redo(), egui::Button::new("Redo"))
+                .on_hover_text(ui.ctx().format_shortcut(&REDO));
+
+            if redo.clicked() {
+                restore = Some(self.jump(self.current + 1));
+            }
+        });
+
+        egui::CollapsingHeader::new("History").show(ui, |ui| {
+            let mut jump_to = None;
+
+            // newest first
+            for (i, entry) in self.entries.iter().enumerate().rev() {
+                let label = ui.selectable_label(i == self.current, describe(&entry.changed));
+
+                if label.clicked() && i != self.current {
+                    jump_to = Some(i);
+                }
+            }
+
+            if let Some(i) = jump_to {
+                restore = Some(self.jump(i));
+            }
+        });
+
+        restore
+    }
+}
+
+fn changed_keys(
+    old: &[(&'static str, String)],
+    new: &[(&'static str, String)],
+) -> Vec<&'static str> {
+    new.iter()
+        .zip(old)
+        .filter(|(new, old)| new != old)
+        .map(|((key, _), _)| *key)
+        .collect()
+}
+
+/// Lists the changed keys, like "frequency, octaves". The first entry has none.
+fn describe(changed: &[&'static str]) -> String {
+    if changed.is_empty() {
+        return "Start".to_string();
+    }
+
+    let mut label = changed
+        .iter()
+        .take(MAX_LABEL_FIELDS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if changed.len() > MAX_LABEL_FIELDS {
+        label += &format!(" and {} more", changed.len() - MAX_LABEL_FIELDS);
+    }
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::DEFAULT_SETTINGS;
+
+    fn record_at(history: &mut History, time: f64, frequency: f32) {
+        let mut settings = DEFAULT_SETTINGS;
+        settings.config.frequency = frequency;
+
+        let input = egui::RawInput {
+            time: Some(time),
+            ..Default::default()
+        };
+
+        let _ = egui::Context::default().run(input, |ctx| history.record(ctx, &settings));
+    }
+
+    #[test]
+    fn merges_quick_changes_of_the_same_fields() {
+        let mut history = History::default();
+        record_at(&mut history, 0.0, 1.0);
+
+        // the steps of a scroll zoom
+        for step in 1..20 {
+            record_at(
+                &mut history,
+                1.0 + step as f64 * 0.02,
+                1.0 + step as f32 * 0.1,
+            );
+        }
+
+        assert_eq!(history.entries.len(), 2);
+
+        record_at(&mut history, 5.0, 4.0);
+        assert_eq!(history.entries.len(), 3);
+    }
+}
@@ -0,0 +1,164 @@
+//! Shows the raw sample values under the cursor and at pinned probe points.
+
+use eframe::egui;
+
+use crate::{
+    sampling,
+    settings::{Dimension, Settings},
+};
+
+#[derive(Default)]
+pub struct Inspector {
+    /// Pinned pixel coordinates.
+    probes: Vec<[usize; 2]>,
+}
+
+impl Inspector {
+    /// Shows a tooltip for the hovered pixel and pins a probe when the preview is clicked.
+    ///
+    /// `settings` and `values` are the ones of the render shown in `response`.
+    pub fn interact(&mut self, response: &egui::Response, settings: &Settings, values: &[f32]) {
+        let size = settings.texture_size;
+
+        if let Some(pixel) = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked())
+            .and_then(|pos| pixel_at(response.rect, size, pos))
+        {
+            self.probes.push(pixel);
+        }
+
+        if response.dragged() {
+            return;
+        }
+
+        let Some(pixel) = response
+            .hover_pos()
+            .and_then(|pos| pixel_at(response.rect, size, pos))
+        else {
+            return;
+        };
+
+        response.clone().on_hover_ui_at_pointer(|ui| {
+            sample_grid(ui, "hovered sample", settings, values, pixel);
+        });
+    }
+
+    /// Draws the markers of the pinned probes onto a preview image.
+    pub fn paint_probes(&self, painter: &egui::Painter, rect: egui::Rect, size: usize) {
+        if size == 0 {
+            return;
+        }
+
+        let pixel_size = rect.width() / size as f32;
+
+        for (i, &[x, y]) in self.probes.iter().enumerate() {
+            if x >= size || y >= size {
+                continue;
+            }
+
+            let center = rect.min + egui::vec2(x as f32 + 0.5, y as f32 + 0.5) * pixel_size;
+
+            painter.circle(
+                center,
+                4.0,
+                egui::Color32::BLACK,
+                egui::Stroke::new(1.5_f32, egui::Color32::WHITE),
+            );
+
+            painter.text(
+                center + egui::vec2(6.0, -6.0),
+                egui::Align2::LEFT_BOTTOM,
+                (i + 1).to_string(),
+                egui::FontId::proportional(12.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+
+    /// Lists the pinned probes.
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings, values: &[f32]) {
+        egui::CollapsingHeader::new("Probes").show(ui, |ui| {
+            if self.probes.is_empty() {
+                ui.label("Click the preview to pin a probe.");
+                return;
+            }
+
+            let mut remove = None;
+
+            for (i, &pixel) in self.probes.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.strong(format!("{}", i + 1));
+
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(i);
+                    }
+                });
+
+                sample_grid(ui, ("probe", i), settings, values, pixel);
+            }
+
+            if let Some(i) = remove {
+                self.probes.remove(i);
+            }
+
+            if ui.button("Clear").clicked() {
+                self.probes.clear();
+            }
+        });
+    }
+}
+
+/// Returns the pixel of a `size * size` image displayed in `rect` at `pos`.
+fn pixel_at(rect: egui::Rect, size: usize, pos: egui::Pos2) -> Option<[usize; 2]> {
+    if !rect.contains(pos) || size == 0 {
+        return None;
+    }
+
+    let local = (pos - rect.min) / rect.size() * size as f32;
+    let x = (local.x as usize).min(size - 1);
+    let y = (local.y as usize).min(size - 1);
+    Some([x, y])
+}
+
+fn sample_grid(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    settings: &Settings,
+    values: &[f32],
+    pixel: [usize; 2],
+) {
+    let size = settings.texture_size;
+
+    let dimensions = match settings.dimension {
+        Dimension::D2 => 2,
+        Dimension::D3 => 3,
+        Dimension::D4 => 4,
+    };
+
+    let position = sampling::sample_position(settings, size, pixel);
+    let position = position[..dimensions]
+        .iter()
+        .map(|component| format!("{component:.5}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let value = values
+        .get(pixel[1] * size + pixel[0])
+        .filter(|_| pixel[0] < size && pixel[1] < size)
+        .map_or_else(|| "-".to_string(), |value| format!("{value:?}"));
+
+    egui::Grid::new(id).num_columns(2).show(ui, |ui| {
+        ui.label("Pixel");
+        ui.monospace(format!("{}, {}", pixel[0], pixel[1]));
+        ui.end_row();
+
+        ui.label("Input");
+        ui.monospace(format!("[{position}]"));
+        ui.end_row();
+
+        ui.label("Value");
+        ui.monospace(value);
+        ui.end_row();
+    });
+}
@@ -1,6 +1,29 @@
 #![allow(clippy::collapsible_else_if)]
 #![forbid(unsafe_code)]
-#![feature(portable_simd)]
 
+mod animation;
 mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+mod code;
+mod colormap;
+mod comparison;
+mod contours;
+mod cross_section;
+mod export;
+mod fixed_list;
+mod heightmap;
+mod history;
+mod inspector;
+mod presets;
+mod preview;
+mod render;
+mod sampling;
+mod settings;
+mod settings_file;
+mod shading;
+mod simd_diff;
+mod stats;
+#[cfg(target_arch = "wasm32")]
+pub mod worker;
 pub use app::App;
@@ -7,6 +7,17 @@ fn main() -> eframe::Result<()> {
 
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let mut args = std::env::args().skip(1).peekable();
+
+    if args.next_if(|arg| arg == "render").is_some() {
+        if let Err(e) = noise_functions_demo::cli::run(args) {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([310.0, 702.0])
@@ -35,10 +46,11 @@ fn main() {
     let web_options = eframe::WebOptions::default();
 
     wasm_bindgen_futures::spawn_local(async {
-        let document = web_sys::window()
-            .expect("No window")
-            .document()
-            .expect("No document");
+        let window = web_sys::window().expect("No window");
+        let document = window.document().expect("No document");
+
+        // The settings of a shared link.
+        let url_fragment = window.location().hash().unwrap_or_default();
 
         let canvas = document
             .get_element_by_id("the_canvas_id")
@@ -50,7 +62,12 @@ fn main() {
             .start(
                 canvas,
                 web_options,
-                Box::new(|cc| Ok(Box::new(noise_functions_demo::App::new(cc)))),
+                Box::new(move |cc| {
+                    Ok(Box::new(noise_functions_demo::App::from_url_fragment(
+                        cc,
+                        &url_fragment,
+                    )))
+                }),
             )
             .await;
 
@@ -0,0 +1,351 @@
+//! Built-in and user defined presets of the settings.
+
+use std::borrow::Cow;
+
+use eframe::egui;
+use noise_functions_config::{Config, Modifier, Noise};
+
+use crate::{
+    colormap::Colormap,
+    settings::{DEFAULT_CONFIG, DEFAULT_SETTINGS, Settings, View},
+    shading::Shading,
+};
+
+const PRESETS_KEY: &str = "presets";
+
+pub struct Preset {
+    pub name: Cow<'static, str>,
+    pub settings: Settings,
+}
+
+/// The presets that ship with the app, the first one being the default settings.
+pub const BUILTIN_PRESETS: &[Preset] = &[
+    Preset {
+        name: Cow::Borrowed("Default"),
+        settings: DEFAULT_SETTINGS,
+    },
+    Preset {
+        name: Cow::Borrowed("Clouds"),
+        settings: Settings {
+            config: Config {
+                noise: Noise::OpenSimplex2s,
+                frequency: 2.0,
+                fractal: true,
+                octaves: 6,
+                gain: 0.5,
+                ..DEFAULT_CONFIG
+            },
+            ..DEFAULT_SETTINGS
+        },
+    },
+    Preset {
+        name: Cow::Borrowed("Marble"),
+        settings: Settings {
+            config: Config {
+                noise: Noise::Perlin,
+                frequency: 1.5,
+                modifier: Modifier::TriangleWave,
+                triangle_wave_frequency: 3.0,
+                fractal: true,
+                octaves: 4,
+                ..DEFAULT_CONFIG
+            },
+            ..DEFAULT_SETTINGS
+        },
+    },
+    Preset {
+        name: Cow::Borrowed("Cellular Stones"),
+        settings: Settings {
+            config: Config {
+                noise: Noise::CellDistance,
+                frequency: 6.0,
+                jitter: 0.8,
+                ..DEFAULT_CONFIG
+            },
+            colormap: Colormap::Magma,
+            shading: Shading::Hillshade,
+            ..DEFAULT_SETTINGS
+        },
+    },
+    Preset {
+        name: Cow::Borrowed("Ridged Mountains"),
+        settings: Settings {
+            config: Config {
+                noise: Noise::OpenSimplex2,
+                frequency: 2.0,
+                modifier: Modifier::Ridged,
+                fractal: true,
+                octaves: 6,
+                weighted_strength: 0.5,
+                ..DEFAULT_CONFIG
+            },
+            colormap: Colormap::Terrain,
+            view: View::Heightmap,
+            shading: Shading::Hillshade,
+            ..DEFAULT_SETTINGS
+        },
+    },
+];
+
+#[derive(Default)]
+pub struct Presets {
+    user: Vec<Preset>,
+    /// Name for saving the current settings.
+    name: String,
+    /// Index and new name of the preset being renamed.
+    renaming: Option<(usize, String)>,
+    error: Option<String>,
+    unreadable: Unreadable,
+}
+
+impl Presets {
+    /// Restores the user presets saved by [`Presets::save`].
+    ///
+    /// Presets that can't be read are skipped and kept as they are.
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let (user, unreadable) = storage
+            .and_then(|storage| storage.get_string(PRESETS_KEY))
+            .map(|text| from_text(&text))
+            .unwrap_or_default();
+
+        for error in &unreadable.errors {
+            log::warn!("failed to restore {error}");
+        }
+
+        Self {
+            user,
+            unreadable,
+            ..Default::default()
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        // the unreadable text goes first, it may start with lines that belong to no preset
+        storage.set_string(
+            PRESETS_KEY,
+            format!("{}{}", self.unreadable.text, to_text(&self.user)),
+        );
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.user.iter().position(|preset| preset.name == name)
+    }
+
+    /// Shows the preset list. Returns the settings of the preset to load.
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings) -> Option<Settings> {
+        let mut load = None;
+
+        egui::CollapsingHeader::new("Presets").show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for preset in BUILTIN_PRESETS {
+                    if ui.button(&*preset.name).clicked() {
+                        load = Some(preset.settings.clone());
+                    }
+                }
+            });
+
+            ui.separator();
+
+            if self.user.is_empty() {
+                ui.weak("No saved presets.");
+            }
+
+            let mut delete = None;
+
+            egui::Grid::new("user presets")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (i, preset) in self.user.iter().enumerate() {
+                        match &mut self.renaming {
+                            Some((index, name)) if *index == i => {
+                                // keep the focus until the rename is confirmed or canceled
+                                ui.text_edit_singleline(name).request_focus();
+                            }
+                            _ => {
+                                if ui.button(&*preset.name).clicked() {
+                                    load = Some(preset.settings.clone());
+                                }
+                            }
+                        }
+
+                        if ui.button("✏").on_hover_text("Rename").clicked() {
+                            self.renaming = Some((i, preset.name.to_string()));
+                        }
+
+                        if ui.button("🗙").on_hover_text("Delete").clicked() {
+                            delete = Some(i);
+                        }
+
+                        ui.end_row();
+                    }
+                });
+
+            if let Some(i) = delete {
+                self.user.remove(i);
+                self.renaming = None;
+            }
+
+            self.apply_rename(ui.ctx());
+
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.name).hint_text("Name"));
+
+                let name = self.name.trim();
+
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+                    .on_hover_text(
+                        "Saves the current settings, replacing a preset with the same name.",
+                    )
+                    .clicked()
+                {
+                    let preset = Preset {
+                        name: Cow::Owned(name.to_string()),
+                        settings: settings.clone(),
+                    };
+
+                    match self.position(name) {
+                        Some(i) => self.user[i] = preset,
+                        None => self.user.push(preset),
+                    }
+
+                    self.name.clear();
+                }
+            });
+
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            if !self.unreadable.errors.is_empty() {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "Some saved presets can't be read and are kept unchanged:",
+                );
+
+                for error in &self.unreadable.errors {
+                    ui.weak(error);
+                }
+            }
+        });
+
+        load
+    }
+
+    /// Renames the preset once the name is confirmed with enter, escape cancels.
+    fn apply_rename(&mut self, ctx: &egui::Context) {
+        let Some((index, name)) = &self.renaming else {
+            return;
+        };
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.renaming = None;
+            return;
+        }
+
+        if !ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+            return;
+        }
+
+        let name = name.trim().to_string();
+        let index = *index;
+
+        self.error = if name.is_empty() {
+            Some("the name can't be empty".into())
+        } else if self.position(&name).is_some_and(|i| i != index) {
+            Some(format!("a preset named {name:?} already exists"))
+        } else {
+            self.user[index].name = Cow::Owned(name);
+            self.renaming = None;
+            None
+        };
+    }
+}
+
+/// Writes the presets as a `[name]` line followed by their settings in the format of
+/// [`Settings::to_text`].
+fn to_text(presets: &[Preset]) -> String {
+    presets
+        .iter()
+        .map(|preset| format!("[{}]\n{}", preset.name, preset.settings.to_text()))
+        .collect()
+}
+
+/// Saved presets that can't be read, for example after a setting was renamed.
+///
+/// They are written back unchanged so they aren't lost.
+#[derive(Default)]
+struct Unreadable {
+    text: String,
+    errors: Vec<String>,
+}
+
+/// Parses presets in the format of [`to_text`], skipping the ones that can't be read.
+fn from_text(text: &str) -> (Vec<Preset>, Unreadable) {
+    // the first section holds the lines before the first `[name]`
+    let mut sections = vec![(None, String::new())];
+
+    for line in text.lines() {
+        let header = line
+            .trim()
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'));
+
+        match header {
+            Some(name) => sections.push((Some(name), String::new())),
+            None => {
+                let (_, settings) = sections.last_mut().expect("there is always a section");
+                settings.push_str(line);
+                settings.push('\n');
+            }
+        }
+    }
+
+    let mut presets = Vec::new();
+    let mut unreadable = Unreadable::default();
+
+    for (name, text) in sections {
+        let preset = match name {
+            None if text.trim().is_empty() => continue,
+            None => Err("expected a `[name]` line".to_string()),
+            Some(name) => Settings::from_text(&text).map(|settings| Preset {
+                name: Cow::Owned(name.to_string()),
+                settings,
+            }),
+        };
+
+        match preset {
+            Ok(preset) => presets.push(preset),
+            Err(e) => {
+                if let Some(name) = name {
+                    unreadable.text.push_str(&format!("[{name}]\n"));
+                }
+
+                unreadable.text.push_str(&text);
+                unreadable
+                    .errors
+                    .push(format!("preset {:?}: {e}", name.unwrap_or("")));
+            }
+        }
+    }
+
+    (presets, unreadable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unreadable_presets() {
+        let text = "[good]\nseed = 1\n[bad]\nrenamed_field = 2\n";
+        let (presets, unreadable) = from_text(text);
+
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].name, "good");
+        assert_eq!(presets[0].settings.config.seed, 1);
+
+        assert_eq!(unreadable.text, "[bad]\nrenamed_field = 2\n");
+        assert_eq!(unreadable.errors.len(), 1);
+    }
+}
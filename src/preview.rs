@@ -0,0 +1,122 @@
+//! Mouse interaction with the preview image.
+
+use eframe::egui;
+
+use crate::{
+    colormap, sampling,
+    settings::{Dimension, Settings},
+};
+
+/// How much the zoom changes per scrolled point.
+const ZOOM_SPEED: f32 = 0.002;
+
+/// Returns the factor the view should be magnified by for the scrolling over `response`.
+///
+/// The scroll is consumed so it doesn't also scroll the surrounding scroll area.
+pub fn scroll_zoom(response: &egui::Response) -> f32 {
+    if response.hover_pos().is_none() {
+        return 1.0;
+    }
+
+    let scroll = response.ctx.input(|i| i.smooth_scroll_delta.y);
+
+    if scroll == 0.0 {
+        return 1.0;
+    }
+
+    response.ctx.input_mut(|i| i.smooth_scroll_delta.y = 0.0);
+    (scroll * ZOOM_SPEED).exp()
+}
+
+/// Pans the view when dragging the preview and zooms around the cursor when scrolling.
+///
+/// The same pan and zoom is applied to all `settings`, so linked previews stay aligned.
+/// Returns `true` if the settings changed.
+pub fn pan_and_zoom(settings: &mut [&mut Settings], response: &egui::Response) -> bool {
+    let rect = response.rect;
+
+    if rect.width() <= 0.0 {
+        return false;
+    }
+
+    let mut delta = egui::Vec2::ZERO;
+
+    if response.dragged_by(egui::PointerButton::Primary) {
+        response.ctx.set_cursor_icon(egui::CursorIcon::Grabbing);
+        delta = response.drag_delta();
+    } else if response.hovered() {
+        response.ctx.set_cursor_icon(egui::CursorIcon::Grab);
+    }
+
+    let zoom = scroll_zoom(response);
+    let mut changed = false;
+
+    for settings in settings {
+        changed |= apply_pan_and_zoom(settings, response, delta, zoom);
+    }
+
+    changed
+}
+
+fn apply_pan_and_zoom(
+    settings: &mut Settings,
+    response: &egui::Response,
+    delta: egui::Vec2,
+    zoom: f32,
+) -> bool {
+    let rect = response.rect;
+    let (scale, offset) = sampling::pixel_mapping(settings.config.tileable, rect.width());
+    let mut changed = false;
+
+    if delta != egui::Vec2::ZERO {
+        settings.x -= delta.x * scale;
+        settings.y -= delta.y * scale;
+        changed = true;
+    }
+
+    let Some(pointer) = response.hover_pos().filter(|_| zoom != 1.0) else {
+        return changed;
+    };
+
+    let old_frequency = settings.config.frequency;
+    let new_frequency = old_frequency / zoom;
+    let ratio = old_frequency / new_frequency;
+
+    // keep the noise under the cursor in place
+    let cursor = (pointer - rect.min) * scale + egui::Vec2::splat(offset);
+    settings.x = (cursor.x + settings.x) * ratio - cursor.x;
+    settings.y = (cursor.y + settings.y) * ratio - cursor.y;
+
+    // stay on the same slice
+    if matches!(settings.dimension, Dimension::D3 | Dimension::D4) {
+        settings.z *= ratio;
+    }
+
+    if settings.dimension == Dimension::D4 {
+        settings.w *= ratio;
+    }
+
+    settings.config.frequency = new_frequency;
+
+    if settings.link_tile_size_to_frequency {
+        settings.config.tile_width = new_frequency;
+        settings.config.tile_height = new_frequency;
+    }
+
+    true
+}
+
+/// Explains the colors of `highlight_out_of_range`.
+pub fn out_of_range_legend(ui: &mut egui::Ui) {
+    ui.horizontal_wrapped(|ui| {
+        for (color, label) in [
+            (colormap::CLIPPED_HIGH_COLOR, "above 1"),
+            (colormap::CLIPPED_LOW_COLOR, "below -1"),
+            (colormap::NON_FINITE_COLOR, "NaN or infinite"),
+        ] {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, color);
+            ui.label(label);
+        }
+    });
+}
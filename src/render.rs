@@ -0,0 +1,254 @@
+//! Renders the preview in the background so sampling doesn't block the ui.
+//!
+//! On native a thread is spawned for each render. On the web the rows are sampled on a web
+//! worker, see [`crate::worker`].
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
+use web_time::{Duration, Instant};
+
+use eframe::egui;
+
+use crate::{colormap::Mask, contours, sampling, settings::Settings, stats::Stats};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::sampling::Progress;
+
+#[cfg(target_arch = "wasm32")]
+use crate::worker;
+
+/// A finished render.
+pub struct Render {
+    pub settings: Settings,
+    pub values: Vec<f32>,
+    /// `None` for a renderer created with [`Renderer::without_stats`].
+    pub stats: Option<Stats>,
+    pub pixels: Vec<egui::Color32>,
+    /// Isolines in pixel coordinates, empty unless `settings.contours` is set.
+    pub contours: Vec<[egui::Pos2; 2]>,
+    pub size: usize,
+    pub success: bool,
+    pub elapsed: Duration,
+    pub threads: usize,
+}
+
+impl Render {
+    /// Shows the pixels in `texture` and returns the values to keep around.
+    pub fn upload(self, texture: &mut egui::TextureHandle) -> Cache {
+        texture.set(
+            egui::ColorImage {
+                size: [self.size; 2],
+                pixels: self.pixels,
+            },
+            egui::TextureOptions::NEAREST,
+        );
+
+        Cache {
+            settings: self.settings,
+            values: self.values,
+            stats: self.stats,
+            contours: self.contours,
+        }
+    }
+}
+
+/// The values of the last finished render.
+pub struct Cache {
+    pub settings: Settings,
+    pub values: Vec<f32>,
+    pub stats: Option<Stats>,
+    pub contours: Vec<[egui::Pos2; 2]>,
+}
+
+#[derive(Default)]
+pub struct Renderer {
+    job: Option<Job>,
+    skip_stats: bool,
+}
+
+struct Job {
+    size: usize,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    progress: Arc<Progress>,
+    #[cfg(not(target_arch = "wasm32"))]
+    receiver: std::sync::mpsc::Receiver<Render>,
+
+    #[cfg(target_arch = "wasm32")]
+    sampling: worker::Sampling,
+    #[cfg(target_arch = "wasm32")]
+    settings: Settings,
+    #[cfg(target_arch = "wasm32")]
+    start: Instant,
+    #[cfg(target_arch = "wasm32")]
+    stats: bool,
+}
+
+impl Renderer {
+    /// Creates a renderer for images that are only saved, which skips computing the [`Stats`].
+    pub fn without_stats() -> Self {
+        Self {
+            job: None,
+            skip_stats: true,
+        }
+    }
+
+    /// Starts rendering `settings`, canceling the render that is in progress.
+    pub fn start(&mut self, settings: &Settings) {
+        self.cancel();
+
+        let settings = settings.clone();
+        let size = settings.texture_size;
+        let stats = !self.skip_stats;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let job = {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let progress = Arc::new(Progress::default());
+            let thread_progress = progress.clone();
+
+            std::thread::spawn(move || {
+                let threads = sampling::threads(&settings);
+
+                let mut values = vec![0.0; size * size];
+                let start = Instant::now();
+                let success = sampling::sample_parallel(
+                    &settings,
+                    size,
+                    &mut values,
+                    &thread_progress,
+                    threads,
+                );
+                let elapsed = start.elapsed();
+
+                if !thread_progress.is_canceled() {
+                    // the receiver is gone if the job was replaced in the meantime
+                    sender
+                        .send(finish(settings, values, success, elapsed, threads, stats))
+                        .ok();
+                }
+            });
+
+            Job {
+                size,
+                progress,
+                receiver,
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let job = Job {
+            size,
+            sampling: worker::Sampling::start(&settings),
+            settings,
+            start: Instant::now(),
+            stats,
+        };
+
+        self.job = Some(job);
+    }
+
+    /// Returns the render if it finished since the last call.
+    pub fn poll(&mut self) -> Option<Render> {
+        let job = self.job.as_mut()?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let render = match job.receiver.try_recv() {
+            Ok(render) => Some(render),
+            Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => None,
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        let render = {
+            let (values, success) = job.sampling.poll()?;
+
+            Some(finish(
+                job.settings.clone(),
+                values,
+                success,
+                job.start.elapsed(),
+                job.sampling.threads(),
+                job.stats,
+            ))
+        };
+
+        self.job = None;
+        render
+    }
+
+    /// Returns the progress of the current render in `0.0..=1.0`, or `None` if there is none.
+    pub fn progress(&self) -> Option<f32> {
+        let job = self.job.as_ref()?;
+
+        if job.size == 0 {
+            return Some(1.0);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let rows = job.progress.rows();
+        #[cfg(target_arch = "wasm32")]
+        let rows = job.sampling.rows();
+
+        Some(rows as f32 / job.size as f32)
+    }
+
+    /// Returns `true` if a render is in progress.
+    pub fn is_busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    fn cancel(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(job) = &self.job {
+            job.progress.cancel();
+        }
+
+        // on the web dropping the job drops the batches that aren't sent to the worker yet
+        self.job = None;
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        // stop sampling a render nobody waits for
+        self.cancel();
+    }
+}
+
+fn finish(
+    settings: Settings,
+    values: Vec<f32>,
+    success: bool,
+    elapsed: Duration,
+    threads: usize,
+    stats: bool,
+) -> Render {
+    let mut pixels = vec![egui::Color32::BLACK; values.len()];
+    sampling::colorize(&settings, settings.texture_size, &values, &mut pixels);
+
+    let contours = if settings.contours {
+        contours::contours(&values, settings.texture_size, &settings.thresholds)
+    } else {
+        Vec::new()
+    };
+
+    let thresholds = if settings.mask == Mask::None {
+        &[][..]
+    } else {
+        settings.thresholds.as_slice()
+    };
+
+    Render {
+        size: settings.texture_size,
+        contours,
+        stats: stats.then(|| Stats::new(&values, thresholds)),
+        settings,
+        values,
+        pixels,
+        success,
+        elapsed,
+        threads,
+    }
+}
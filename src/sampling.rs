@@ -0,0 +1,273 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use eframe::egui;
+use noise_functions_config::{Config, noise_functions::Noise as _};
+
+use crate::{
+    colormap,
+    settings::{Dimension, Settings},
+    shading,
+};
+
+/// Explains why sampling failed when the noise type does not support the dimension / tileable
+/// combination.
+pub const UNSUPPORTED: &str = "dimension/tileable not available for this noise type";
+
+/// Samples the noise described by `settings` into `values`, a `size * size` image.
+///
+/// The sampled area does not depend on `size`, so this can be used to render the
+/// preview at a higher resolution.
+///
+/// Uses every core unless `settings.multithreaded` is turned off.
+///
+/// Returns `false` if the noise type does not support the dimension / tileable combination.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn sample(settings: &Settings, size: usize, values: &mut [f32]) -> bool {
+    sample_parallel(
+        settings,
+        size,
+        values,
+        &Progress::default(),
+        threads(settings),
+    )
+}
+
+/// Used to observe and cancel sampling from another thread.
+#[derive(Default)]
+pub struct Progress {
+    rows: AtomicUsize,
+    canceled: AtomicBool,
+}
+
+impl Progress {
+    /// Returns the number of sampled rows.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn rows(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cancel(&self) {
+        self.canceled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.canceled.load(Ordering::Relaxed)
+    }
+}
+
+/// Returns the `(scale, offset)` that maps a pixel coordinate of an image that is `size` pixels
+/// wide to a sample coordinate, before the x / y shift is applied.
+pub fn pixel_mapping(tileable: bool, size: f32) -> (f32, f32) {
+    // tileable noise repeats in the 0..1 range, otherwise we show -1..1
+    if tileable {
+        (1.0 / size, 0.0)
+    } else {
+        (2.0 / size, -1.0)
+    }
+}
+
+/// Returns the coordinate that is passed to the sampler for the pixel `[x, y]` of a `size * size` image.
+///
+/// Only the first 2, 3 or 4 components are used, depending on the dimension.
+pub fn sample_position(settings: &Settings, size: usize, [x, y]: [usize; 2]) -> [f32; 4] {
+    let (scalar, offset) = pixel_mapping(settings.config.tileable, size as f32);
+
+    [
+        x as f32 * scalar + offset + settings.x,
+        y as f32 * scalar + offset + settings.y,
+        settings.z,
+        settings.w,
+    ]
+}
+
+/// Evaluates `$body` with `$sample` bound to a `Fn(f32, f32) -> f32` that samples the noise of
+/// `$settings` at `x`, `y` and the z and w of the settings, using the sampler the settings pick.
+///
+/// Evaluates to `None` if the noise type does not support the dimension / tileable combination.
+macro_rules! with_sampler {
+    ($settings:expr, |$sample:ident| $body:expr) => {{
+        let settings: &Settings = $settings;
+        let Settings { z, w, .. } = *settings;
+        let config = &settings.config;
+
+        match (settings.simd, settings.dimension) {
+            (true, Dimension::D2) => config.sampler2a().map(|sampler| {
+                let $sample = move |x, y| sampler.sample2a([x, y]);
+                $body
+            }),
+            (true, Dimension::D3) => config.sampler3a().map(|sampler| {
+                let $sample = move |x, y| sampler.sample3a([x, y, z, 0.0]);
+                $body
+            }),
+            (true, Dimension::D4) => config.sampler4a().map(|sampler| {
+                let $sample = move |x, y| sampler.sample4a([x, y, z, w]);
+                $body
+            }),
+            (false, Dimension::D2) => config.sampler2().map(|sampler| {
+                let $sample = move |x, y| sampler.sample2([x, y]);
+                $body
+            }),
+            (false, Dimension::D3) => config.sampler3().map(|sampler| {
+                let $sample = move |x, y| sampler.sample3([x, y, z]);
+                $body
+            }),
+            (false, Dimension::D4) => config.sampler4().map(|sampler| {
+                let $sample = move |x, y| sampler.sample4([x, y, z, w]);
+                $body
+            }),
+        }
+    }};
+}
+
+/// Returns a function that samples the noise of `settings` at a pixel position of a `size * size`
+/// image. Unlike [`sample_position`] the position doesn't need to be a whole pixel.
+///
+/// This is slower than [`sample`] and meant for a few samples, like a cross section.
+/// Returns `None` if the noise type does not support the dimension / tileable combination.
+pub fn point_sampler(
+    settings: &Settings,
+    size: usize,
+) -> Option<Box<dyn Fn([f32; 2]) -> f32 + '_>> {
+    let (scalar, offset) = pixel_mapping(settings.config.tileable, size as f32);
+    let Settings { x, y, .. } = *settings;
+
+    with_sampler!(settings, |sample| {
+        Box::new(move |[px, py]: [f32; 2]| {
+            sample(px * scalar + offset + x, py * scalar + offset + y)
+        }) as Box<dyn Fn([f32; 2]) -> f32 + '_>
+    })
+}
+
+/// Like [`sample`] but only samples the rows starting at `first_row` that fit into `values`.
+///
+/// Stops early if `progress` is canceled.
+pub fn sample_rows(
+    settings: &Settings,
+    size: usize,
+    first_row: usize,
+    values: &mut [f32],
+    progress: &Progress,
+) -> bool {
+    fn sample(
+        values: &mut [f32],
+        settings: &Settings,
+        size: usize,
+        first_row: usize,
+        progress: &Progress,
+        f: impl Fn(f32, f32) -> f32,
+    ) {
+        let Settings {
+            config: Config { tileable, .. },
+            x: x_shift,
+            y: y_shift,
+            ..
+        } = *settings;
+
+        let (scalar, offset) = pixel_mapping(tileable, size as f32);
+
+        for (row, values) in values.chunks_mut(size).enumerate() {
+            if progress.is_canceled() {
+                return;
+            }
+
+            let y = (first_row + row) as f32 * scalar + offset + y_shift;
+
+            for (x, value) in values.iter_mut().enumerate() {
+                let x = x as f32 * scalar + offset + x_shift;
+                *value = f(x, y);
+            }
+
+            progress.rows.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    with_sampler!(settings, |sampler| {
+        sample(values, settings, size, first_row, progress, sampler)
+    })
+    .is_some()
+}
+
+/// Returns the number of threads to sample `settings` with.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn threads(settings: &Settings) -> usize {
+    if settings.multithreaded {
+        std::thread::available_parallelism().map_or(1, std::num::NonZero::get)
+    } else {
+        1
+    }
+}
+
+/// Like [`sample_rows`] for a whole image but splits the rows across `threads` threads.
+///
+/// The web version splits the rows across web workers instead, see [`crate::worker`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn sample_parallel(
+    settings: &Settings,
+    size: usize,
+    values: &mut [f32],
+    progress: &Progress,
+    threads: usize,
+) -> bool {
+    if size == 0 || threads <= 1 {
+        return sample_rows(settings, size, 0, values, progress);
+    }
+
+    let rows_per_thread = size.div_ceil(threads);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = values
+            .chunks_mut(rows_per_thread * size)
+            .enumerate()
+            .map(|(i, values)| {
+                let first_row = i * rows_per_thread;
+                scope.spawn(move || sample_rows(settings, size, first_row, values, progress))
+            })
+            .collect();
+
+        handles.into_iter().fold(true, |success, handle| {
+            success & handle.join().expect("sampling thread panicked")
+        })
+    })
+}
+
+/// Converts sampled `values` to colors using the colormap or mask of `settings`.
+///
+/// Values outside of `-1..=1` saturate unless `highlight_out_of_range` is set.
+/// The `values` are a `size * size` image, the shading needs its neighboring pixels.
+pub fn colorize(settings: &Settings, size: usize, values: &[f32], pixels: &mut [egui::Color32]) {
+    let lut = settings.colormap.lut(&settings.gradient);
+
+    let mut thresholds = settings.thresholds.as_slice().to_vec();
+    thresholds.sort_by(f32::total_cmp);
+
+    let band_colors = settings.mask.band_colors(&lut, thresholds.len() + 1);
+
+    let color = |value: f32| {
+        if band_colors.is_empty() {
+            lut[colormap::lut_index(value)]
+        } else {
+            band_colors[thresholds.partition_point(|&threshold| threshold < value)]
+        }
+    };
+
+    if settings.highlight_out_of_range {
+        for (pixel, &value) in pixels.iter_mut().zip(values) {
+            *pixel = if !value.is_finite() {
+                colormap::NON_FINITE_COLOR
+            } else if value > 1.0 {
+                colormap::CLIPPED_HIGH_COLOR
+            } else if value < -1.0 {
+                colormap::CLIPPED_LOW_COLOR
+            } else {
+                color(value)
+            };
+        }
+    } else {
+        for (pixel, &value) in pixels.iter_mut().zip(values) {
+            *pixel = color(value);
+        }
+    }
+
+    shading::shade(settings, size, values, pixels);
+}
@@ -0,0 +1,538 @@
+use std::hash::Hash;
+
+use eframe::egui;
+use noise_functions_config::{Config, Improve, Modifier, Noise};
+
+use crate::{
+    colormap::{Colormap, Gradient, GradientStop, MAX_GRADIENT_STOPS, Mask},
+    contours::{MAX_THRESHOLDS, Thresholds},
+    shading::Shading,
+};
+
+#[derive(Clone)]
+pub struct Settings {
+    pub config: Config,
+    pub texture_size: usize,
+    pub dimension: Dimension,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+    pub simd: bool,
+    pub multithreaded: bool,
+    pub show_tiles: bool,
+    pub link_tile_size_to_frequency: bool,
+    pub colormap: Colormap,
+    pub gradient: Gradient,
+    pub highlight_out_of_range: bool,
+    pub view: View,
+    pub height_scale: f32,
+    pub shading: Shading,
+    /// Degrees clockwise from the top of the image.
+    pub light_azimuth: f32,
+    /// Degrees above the horizon.
+    pub light_elevation: f32,
+    pub contours: bool,
+    pub thresholds: Thresholds,
+    pub mask: Mask,
+}
+
+pub const DEFAULT_CONFIG: Config = Config {
+    noise: Noise::OpenSimplex2,
+    seed: 0,
+    frequency: 3.0,
+
+    // modifiers
+    modifier: Modifier::None,
+    triangle_wave_frequency: 2.0,
+
+    // fractal
+    fractal: false,
+    lacunarity: 2.0,
+    octaves: 3,
+    gain: 0.5,
+    weighted_strength: 0.0,
+
+    // open simplex 2
+    improve: Improve::None,
+
+    // cell
+    jitter: 1.0,
+
+    // tiling
+    tileable: false,
+    tile_width: 3.0,
+    tile_height: 3.0,
+};
+
+/// Also the first of the [built-in presets](crate::presets::BUILTIN_PRESETS).
+pub const DEFAULT_SETTINGS: Settings = Settings {
+    config: DEFAULT_CONFIG,
+    texture_size: 295,
+    dimension: Dimension::D2,
+    x: 0.0,
+    y: 0.0,
+    z: 0.0,
+    w: 0.0,
+    simd: false,
+    multithreaded: true,
+    show_tiles: true,
+    link_tile_size_to_frequency: true,
+    colormap: Colormap::Grayscale,
+    gradient: Gradient::DEFAULT,
+    highlight_out_of_range: false,
+    view: View::Image,
+    height_scale: 0.5,
+    shading: Shading::None,
+    light_azimuth: 315.0,
+    light_elevation: 45.0,
+    contours: false,
+    thresholds: Thresholds::DEFAULT,
+    mask: Mask::None,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    D2,
+    D3,
+    D4,
+}
+
+impl Dimension {
+    pub const VARIANTS: &'static [Self] = &[Self::D2, Self::D3, Self::D4];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Dimension::D2 => "2D",
+            Dimension::D3 => "3D",
+            Dimension::D4 => "4D",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum View {
+    Image,
+    Heightmap,
+}
+
+impl View {
+    pub const VARIANTS: &'static [Self] = &[Self::Image, Self::Heightmap];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            View::Image => "Image",
+            View::Heightmap => "Heightmap",
+        }
+    }
+}
+
+/// Version of the file format written by [`Settings::to_file`].
+///
+/// Bump it when a field changes its meaning and convert older files in [`Settings::from_file`].
+pub const FILE_VERSION: u32 = 1;
+
+impl Settings {
+    /// Parses settings in the format of [`Settings::to_text`], which is a TOML document.
+    ///
+    /// Missing fields keep their default value.
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let document = parse_toml(text)?;
+        Self::from_toml(document.as_table())
+    }
+
+    fn from_toml(table: &toml_edit::Table) -> Result<Self, String> {
+        let mut settings = DEFAULT_SETTINGS;
+
+        for (key, item) in table {
+            let value = match item.as_value() {
+                Some(toml_edit::Value::String(value)) => value.value().clone(),
+                Some(toml_edit::Value::Integer(value)) => value.value().to_string(),
+                Some(toml_edit::Value::Float(value)) => value.value().to_string(),
+                Some(toml_edit::Value::Boolean(value)) => value.value().to_string(),
+                _ => {
+                    return Err(format!(
+                        "invalid value for `{key}`: expected a string, number or boolean"
+                    ));
+                }
+            };
+
+            settings.set_field(key, &value)?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Writes the settings as TOML `key = value` lines.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+
+        for (key, value) in self.fields() {
+            let is_plain = value == "true" || value == "false" || value.parse::<f64>().is_ok();
+
+            if is_plain {
+                text.push_str(&format!("{key} = {value}\n"));
+            } else {
+                text.push_str(&format!("{key} = {}\n", quote_toml(&value)));
+            }
+        }
+
+        text
+    }
+
+    /// Parses a settings file written by [`Settings::to_file`], including files of older versions.
+    ///
+    /// Files without a `version` predate it and are read like version 1.
+    pub fn from_file(text: &str) -> Result<Self, String> {
+        let mut document = parse_toml(text)?;
+
+        let version = match document.remove("version") {
+            None => 1,
+            Some(item) => item
+                .as_integer()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or("the version must be an unsigned integer")?,
+        };
+
+        match version {
+            1 => Self::from_toml(document.as_table()),
+            version if version > FILE_VERSION => Err(format!(
+                "the file has version {version} which is newer than the supported version {FILE_VERSION}"
+            )),
+            version => Err(format!("unknown version {version}")),
+        }
+    }
+
+    /// Writes the settings as a versioned file in the format of [`Settings::to_text`].
+    pub fn to_file(&self) -> String {
+        format!(
+            "# noise-functions-demo settings\nversion = {FILE_VERSION}\n\n{}",
+            self.to_text()
+        )
+    }
+
+    /// Parses settings in the format of [`Settings::to_url_fragment`].
+    pub fn from_url_fragment(fragment: &str) -> Result<Self, String> {
+        let mut settings = DEFAULT_SETTINGS;
+
+        for pair in fragment.trim_start_matches('#').split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+
+            let Some((key, value)) = pair.split_once('=') else {
+                return Err(format!("expected `key=value`, got `{pair}`"));
+            };
+
+            settings.set_field(key, value)?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Writes the settings that differ from the default as `key=value` pairs separated by `&`.
+    #[cfg(target_arch = "wasm32")]
+    pub fn to_url_fragment(&self) -> String {
+        self.fields()
+            .into_iter()
+            .zip(DEFAULT_SETTINGS.fields())
+            .filter(|((_, value), (_, default))| value != default)
+            .map(|((key, value), _)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+fn parse_toml(text: &str) -> Result<toml_edit::DocumentMut, String> {
+    text.parse::<toml_edit::DocumentMut>()
+        .map_err(|e| e.to_string().trim_end().to_string())
+}
+
+/// Writes `value` as a TOML basic string.
+fn quote_toml(value: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// The optional range rejects values the ui can't produce, like a texture size that doesn't fit
+/// into memory.
+macro_rules! settings_fields {
+    ($($key:literal => $($field:ident).+ $(in $range:expr)?,)*) => {
+        impl Settings {
+            /// Returns every setting as a key value pair.
+            pub fn fields(&self) -> Vec<(&'static str, String)> {
+                vec![$(($key, Field::write(&self.$($field).+)),)*]
+            }
+
+            pub fn set_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+                match key {
+                    $($key => {
+                        let field = &mut self.$($field).+;
+
+                        Field::read(field, value)
+                            $(.and_then(|()| check_range(field, $range)))?
+                            .map_err(|e| format!("invalid value for `{key}`: {e}"))
+                    })*
+                    _ => Err(format!("unknown field `{key}`")),
+                }
+            }
+        }
+    };
+}
+
+settings_fields! {
+    "noise" => config.noise,
+    "seed" => config.seed,
+    "frequency" => config.frequency,
+    "modifier" => config.modifier,
+    "triangle_wave_frequency" => config.triangle_wave_frequency in 0.5..=3.0,
+    "fractal" => config.fractal,
+    "lacunarity" => config.lacunarity,
+    "octaves" => config.octaves in 1..=8,
+    "gain" => config.gain,
+    "weighted_strength" => config.weighted_strength in 0.0..=1.0,
+    "improve" => config.improve,
+    "jitter" => config.jitter,
+    "tileable" => config.tileable,
+    "tile_width" => config.tile_width,
+    "tile_height" => config.tile_height,
+    "dimension" => dimension,
+    "x" => x,
+    "y" => y,
+    "z" => z,
+    "w" => w,
+    "simd" => simd,
+    "multithreaded" => multithreaded,
+    "texture_size" => texture_size in 0..=1024,
+    "show_tiles" => show_tiles,
+    "link_tile_size_to_frequency" => link_tile_size_to_frequency,
+    "colormap" => colormap,
+    "gradient" => gradient,
+    "highlight_out_of_range" => highlight_out_of_range,
+    "view" => view,
+    "height_scale" => height_scale in 0.0..=10.0,
+    "shading" => shading,
+    "light_azimuth" => light_azimuth in 0.0..=360.0,
+    "light_elevation" => light_elevation in 0.0..=90.0,
+    "contours" => contours,
+    "thresholds" => thresholds,
+    "mask" => mask,
+}
+
+/// A setting that can be converted to and from a string.
+trait Field: Sized {
+    fn write(&self) -> String;
+    fn read(&mut self, value: &str) -> Result<(), String>;
+}
+
+macro_rules! impl_field_for_primitives {
+    ($($ty:ty),*) => {
+        $(
+            impl Field for $ty {
+                fn write(&self) -> String {
+                    self.to_string()
+                }
+
+                fn read(&mut self, value: &str) -> Result<(), String> {
+                    *self = value.parse().map_err(|e| format!("{e}"))?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_field_for_primitives!(bool, i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl Field for f32 {
+    fn write(&self) -> String {
+        self.to_string()
+    }
+
+    fn read(&mut self, value: &str) -> Result<(), String> {
+        let value = value.parse::<f32>().map_err(|e| format!("{e}"))?;
+
+        if !value.is_finite() {
+            return Err(format!("{value} is not a finite number"));
+        }
+
+        *self = value;
+        Ok(())
+    }
+}
+
+/// Reads a finite number, like a plain `f32` field.
+fn read_f32(value: &str) -> Result<f32, String> {
+    let mut number = 0.0;
+    Field::read(&mut number, value.trim())?;
+    Ok(number)
+}
+
+fn check_range<T>(value: &T, range: std::ops::RangeInclusive<T>) -> Result<(), String>
+where
+    T: PartialOrd + std::fmt::Display,
+{
+    if range.contains(value) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{value} is not between {} and {}",
+            range.start(),
+            range.end()
+        ))
+    }
+}
+
+macro_rules! impl_field_for_enums {
+    ($($ty:ty),*) => {
+        $(
+            impl Field for $ty {
+                fn write(&self) -> String {
+                    slug(self.to_str())
+                }
+
+                fn read(&mut self, value: &str) -> Result<(), String> {
+                    *self = *<$ty>::VARIANTS
+                        .iter()
+                        .find(|variant| slug(variant.to_str()) == value)
+                        .ok_or_else(|| format!("unknown variant `{value}`"))?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_field_for_enums!(
+    Noise, Modifier, Improve, Dimension, Colormap, View, Shading, Mask
+);
+
+/// Turns a display name like "Cell Value" into "cell-value".
+fn slug(name: &str) -> String {
+    name.chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Gradients are written as comma separated `position:rrggbbaa` stops.
+impl Field for Gradient {
+    fn write(&self) -> String {
+        self.as_slice()
+            .iter()
+            .map(|stop| {
+                let [r, g, b, a] = stop.color.to_srgba_unmultiplied();
+                format!("{}:{r:02x}{g:02x}{b:02x}{a:02x}", stop.position)
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn read(&mut self, value: &str) -> Result<(), String> {
+        let mut stops = Vec::new();
+
+        for stop in value.split(',') {
+            let (position, color) = stop
+                .split_once(':')
+                .ok_or_else(|| format!("expected `position:rrggbbaa`, got `{stop}`"))?;
+
+            let position = read_f32(position)?;
+            check_range(&position, 0.0..=1.0)?;
+
+            let color = u32::from_str_radix(color.trim(), 16)
+                .ok()
+                .filter(|_| color.trim().len() == 8)
+                .ok_or_else(|| format!("expected a `rrggbbaa` color, got `{color}`"))?;
+            let [r, g, b, a] = color.to_be_bytes();
+
+            stops.push(GradientStop {
+                position,
+                color: egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+            });
+        }
+
+        if stops.len() > MAX_GRADIENT_STOPS {
+            return Err(format!("at most {MAX_GRADIENT_STOPS} stops are supported"));
+        }
+
+        *self = Gradient::from_slice(&stops);
+        Ok(())
+    }
+}
+
+/// Thresholds are written as comma separated numbers.
+impl Field for Thresholds {
+    fn write(&self) -> String {
+        self.as_slice()
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn read(&mut self, value: &str) -> Result<(), String> {
+        let values = value
+            .split(',')
+            .map(read_f32)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if values.len() > MAX_THRESHOLDS {
+            return Err(format!("at most {MAX_THRESHOLDS} thresholds are supported"));
+        }
+
+        *self = Thresholds::from_slice(&values);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_values_outside_of_the_ui_range() {
+        assert!(Settings::from_url_fragment("#texture_size=100000").is_err());
+        assert!(Settings::from_url_fragment("#octaves=4000000000").is_err());
+        assert!(Settings::from_url_fragment("#frequency=inf").is_err());
+        assert!(Settings::from_url_fragment("#texture_size=1024&octaves=8").is_ok());
+
+        assert!(Settings::from_url_fragment("#thresholds=0,nan").is_err());
+        assert!(Settings::from_url_fragment("#gradient=inf:ff0000ff").is_err());
+        assert!(Settings::from_url_fragment("#gradient=1.5:ff0000ff").is_err());
+        assert!(Settings::from_url_fragment("#gradient=0:000000ff,1:ff0000ff").is_ok());
+    }
+
+    #[test]
+    fn reads_toml_files() {
+        let settings = Settings::from_file("version = 1 # comment\nseed = 3 # comment\n").unwrap();
+        assert_eq!(settings.config.seed, 3);
+
+        assert!(Settings::from_file("version = 2\n").is_err());
+        assert_eq!(
+            Settings::from_file("foo = 3\n").err().as_deref(),
+            Some("unknown field `foo`")
+        );
+        assert!(Settings::from_file("seed = [3]\n").is_err());
+
+        let text = Settings {
+            texture_size: 64,
+            ..DEFAULT_SETTINGS
+        }
+        .to_file();
+        assert_eq!(Settings::from_file(&text).unwrap().to_file(), text);
+    }
+}
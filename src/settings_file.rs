@@ -0,0 +1,134 @@
+//! Opening and saving settings files, to keep configurations next to the code that uses them.
+
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc};
+
+use eframe::egui;
+
+use crate::{export, settings::Settings};
+
+const FILE_NAME: &str = "noise.toml";
+
+/// A picked file name and its contents.
+type PickedFile = Result<(String, String), String>;
+
+#[derive(Default)]
+pub struct SettingsFile {
+    status: Option<Result<String, String>>,
+    /// The file picker on the web is asynchronous, the file is read into this.
+    #[cfg(target_arch = "wasm32")]
+    picked: Rc<RefCell<Option<PickedFile>>>,
+}
+
+impl SettingsFile {
+    /// Shows the open and save buttons. Returns the settings of an opened file.
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings) -> Option<Settings> {
+        let mut picked = None;
+
+        egui::CollapsingHeader::new("Settings File").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Open…").clicked() {
+                    picked = self.pick(ui.ctx());
+                }
+
+                if ui.button("Save…").clicked() {
+                    self.status = save(settings).transpose();
+                }
+            });
+
+            export::status_ui(ui, &self.status);
+        });
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            picked = picked.or_else(|| self.picked.borrow_mut().take());
+        }
+
+        let opened = picked?.and_then(|(name, text)| {
+            Settings::from_file(&text)
+                .map(|settings| (name.clone(), settings))
+                .map_err(|e| format!("failed to open {name:?}: {e}"))
+        });
+
+        match opened {
+            Ok((name, settings)) => {
+                self.status = Some(Ok(format!("opened {name:?}")));
+                Some(settings)
+            }
+            Err(error) => {
+                self.status = Some(Err(error));
+                None
+            }
+        }
+    }
+
+    /// Shows the file dialog. Returns `None` if it was canceled.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick(&mut self, _ctx: &egui::Context) -> Option<PickedFile> {
+        let path = rfd::FileDialog::new()
+            .add_filter("Settings", &["toml", "txt"])
+            .pick_file()?;
+
+        let name = path.display().to_string();
+
+        Some(
+            std::fs::read_to_string(&path)
+                .map(|text| (name.clone(), text))
+                .map_err(|e| format!("failed to read {name:?}: {e}")),
+        )
+    }
+
+    /// Opens the file picker, the file arrives in a later frame.
+    #[cfg(target_arch = "wasm32")]
+    fn pick(&mut self, ctx: &egui::Context) -> Option<PickedFile> {
+        let picked = self.picked.clone();
+        let ctx = ctx.clone();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .add_filter("Settings", &["toml", "txt"])
+                .pick_file()
+                .await
+            else {
+                return;
+            };
+
+            let name = file.file_name();
+            let text = String::from_utf8(file.read().await)
+                .map(|text| (name.clone(), text))
+                .map_err(|_| format!("failed to read {name:?}: not valid UTF-8"));
+
+            *picked.borrow_mut() = Some(text);
+            ctx.request_repaint();
+        });
+
+        None
+    }
+}
+
+/// Shows the save dialog and writes the file. Returns `Ok(None)` if it was canceled.
+#[cfg(not(target_arch = "wasm32"))]
+fn save(settings: &Settings) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name(FILE_NAME)
+        .add_filter("Settings", &["toml"])
+        .save_file()
+    else {
+        return Ok(None);
+    };
+
+    std::fs::write(&path, settings.to_file())
+        .map(|()| Some(format!("saved {:?}", path.display().to_string())))
+        .map_err(|e| format!("failed to write {:?}: {e}", path.display().to_string()))
+}
+
+/// Downloads the file.
+#[cfg(target_arch = "wasm32")]
+fn save(settings: &Settings) -> Result<Option<String>, String> {
+    export::save_file(
+        FILE_NAME.as_ref(),
+        "application/toml",
+        settings.to_file().as_bytes(),
+    )
+    .map(|()| Some(format!("saved {FILE_NAME:?}")))
+}
@@ -0,0 +1,149 @@
+//! Shades the noise as if it was a heightfield lit from a direction.
+
+use eframe::egui;
+
+use crate::settings::Settings;
+
+pub type Vec3 = [f32; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shading {
+    None,
+    /// Darkens the colormap by how much each pixel faces the light.
+    Hillshade,
+    /// Shows the tangent space normals, ignoring the colormap.
+    NormalMap,
+}
+
+impl Shading {
+    pub const VARIANTS: &'static [Self] = &[Self::None, Self::Hillshade, Self::NormalMap];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Shading::None => "None",
+            Shading::Hillshade => "Hillshade",
+            Shading::NormalMap => "Normal Map",
+        }
+    }
+}
+
+/// Returns the direction towards the light.
+///
+/// The azimuth is clockwise from the top of the image, the elevation is the
+/// angle above the horizon, both in degrees. The y axis points up, out of the image.
+pub fn light_direction(settings: &Settings) -> Vec3 {
+    let (azimuth_sin, azimuth_cos) = settings.light_azimuth.to_radians().sin_cos();
+    let (elevation_sin, elevation_cos) = settings.light_elevation.to_radians().sin_cos();
+
+    [
+        azimuth_sin * elevation_cos,
+        elevation_sin,
+        -azimuth_cos * elevation_cos,
+    ]
+}
+
+/// Applies `settings.shading` to the colorized `pixels` of a `size * size` image.
+pub fn shade(settings: &Settings, size: usize, values: &[f32], pixels: &mut [egui::Color32]) {
+    if settings.shading == Shading::None || size == 0 {
+        return;
+    }
+
+    let light = light_direction(settings);
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        // keep the highlight colors recognizable
+        if settings.highlight_out_of_range && !(-1.0..=1.0).contains(&values[i]) {
+            continue;
+        }
+
+        let normal = normal_at(settings, size, values, i % size, i / size);
+
+        *pixel = match settings.shading {
+            Shading::None => unreachable!(),
+            Shading::Hillshade => {
+                let light = dot(normal, light).max(0.0);
+                let [r, g, b, _] = pixel.to_array().map(|c| (c as f32 * light) as u8);
+                egui::Color32::from_rgb(r, g, b)
+            }
+            Shading::NormalMap => {
+                // x right, y towards the top of the image, z out of the surface
+                let [r, g, b] = [normal[0], -normal[2], normal[1]]
+                    .map(|c| ((c * 0.5 + 0.5) * 255.0).round() as u8);
+                egui::Color32::from_rgb(r, g, b)
+            }
+        };
+    }
+}
+
+/// Returns the surface normal at a pixel using central differences.
+///
+/// Tileable noise wraps around at the edges, otherwise the differences are one sided there.
+/// The surface has the same proportions as in the heightmap view: the image spans 2 units
+/// and a value of 1 is `height_scale / 2` high.
+fn normal_at(settings: &Settings, size: usize, values: &[f32], x: usize, y: usize) -> Vec3 {
+    let tileable = settings.config.tileable;
+
+    let height = |x: usize, y: usize| {
+        let value = values[y * size + x];
+
+        if value.is_finite() {
+            value.clamp(-1.0, 1.0) * settings.height_scale * 0.5
+        } else {
+            0.0
+        }
+    };
+
+    let neighbors = |i: usize| -> (usize, usize, f32) {
+        if tileable {
+            ((i + size - 1) % size, (i + 1) % size, 2.0)
+        } else {
+            let before = i.saturating_sub(1);
+            let after = (i + 1).min(size - 1);
+            (before, after, (after - before) as f32)
+        }
+    };
+
+    let pixel_size = 2.0 / size as f32;
+    let (left, right, dx) = neighbors(x);
+    let (top, bottom, dz) = neighbors(y);
+
+    let slope_x = if dx == 0.0 {
+        0.0
+    } else {
+        (height(right, y) - height(left, y)) / (dx * pixel_size)
+    };
+
+    let slope_z = if dz == 0.0 {
+        0.0
+    } else {
+        (height(x, bottom) - height(x, top)) / (dz * pixel_size)
+    };
+
+    normalize([-slope_x, 1.0, -slope_z])
+}
+
+pub fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn normalize(a: Vec3) -> Vec3 {
+    let length = dot(a, a).sqrt();
+
+    if length == 0.0 {
+        return [0.0, 1.0, 0.0];
+    }
+
+    a.map(|c| c / length)
+}
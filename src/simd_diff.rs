@@ -0,0 +1,222 @@
+//! Compares the scalar and simd samplers for the current settings.
+//!
+//! Both are rendered in the background like the preview.
+
+use eframe::egui;
+
+use crate::{
+    colormap,
+    render::{Render, Renderer},
+    sampling,
+    settings::Settings,
+};
+
+/// Size of the difference image in the settings panel.
+const IMAGE_SIZE: f32 = 256.0;
+
+#[derive(Default)]
+pub struct SimdDiff {
+    /// The settings of the last comparison apart from `simd`.
+    key: String,
+    scalar: Renderer,
+    simd: Renderer,
+    /// The finished scalar and simd renders, waiting for the other one.
+    renders: [Option<Render>; 2],
+    result: Option<Result<Diff, String>>,
+}
+
+struct Diff {
+    max: f32,
+    max_at: [usize; 2],
+    mean: f64,
+    /// Number of samples that are not bit for bit equal.
+    differing: usize,
+    /// Number of samples where only one of the samplers returns a finite value.
+    non_finite_mismatches: usize,
+    count: usize,
+    texture: egui::TextureHandle,
+}
+
+impl SimdDiff {
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        egui::CollapsingHeader::new("Scalar vs SIMD").show(ui, |ui| {
+            let key = key(settings);
+            let busy = self.scalar.is_busy() || self.simd.is_busy();
+
+            // let the comparison finish and wait until a drag is done instead of restarting it
+            // each frame
+            if key != self.key && !busy && !ui.ctx().input(|i| i.pointer.any_down()) {
+                let mut settings = settings.clone();
+
+                settings.simd = false;
+                self.scalar.start(&settings);
+
+                settings.simd = true;
+                self.simd.start(&settings);
+
+                self.renders = [None, None];
+                self.key = key;
+            }
+
+            if let Some(render) = self.scalar.poll() {
+                self.renders[0] = Some(render);
+            }
+
+            if let Some(render) = self.simd.poll() {
+                self.renders[1] = Some(render);
+            }
+
+            if let [Some(scalar), Some(simd)] = &self.renders {
+                self.result = Some(compare(ui.ctx(), scalar, simd));
+                self.renders = [None, None];
+            }
+
+            let progress = [self.scalar.progress(), self.simd.progress()];
+
+            if progress.iter().any(Option::is_some) {
+                let progress = progress.iter().map(|p| p.unwrap_or(1.0)).sum::<f32>() / 2.0;
+                ui.add(egui::ProgressBar::new(progress).show_percentage());
+
+                // keep polling until both are done
+                ui.ctx().request_repaint();
+            }
+
+            match &self.result {
+                Some(Ok(diff)) => diff.ui(ui),
+                Some(Err(error)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                None => (),
+            }
+        });
+    }
+}
+
+fn key(settings: &Settings) -> String {
+    let mut settings = settings.clone();
+    settings.simd = false;
+    settings.multithreaded = false;
+    settings.to_text()
+}
+
+/// Computes the absolute difference of the renders of both samplers.
+fn compare(ctx: &egui::Context, scalar: &Render, simd: &Render) -> Result<Diff, String> {
+    if !scalar.success {
+        return Err(sampling::UNSUPPORTED.into());
+    }
+
+    if !simd.success {
+        return Err("no simd sampler for this noise type and dimension".into());
+    }
+
+    let size = scalar.size;
+    let (scalar, simd) = (&scalar.values, &simd.values);
+
+    // `None` if only one of them is finite
+    let errors = scalar
+        .iter()
+        .zip(simd)
+        .map(|(&a, &b)| {
+            if a.is_finite() && b.is_finite() {
+                Some((a - b).abs())
+            } else if a.is_finite() == b.is_finite() {
+                Some(0.0)
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let (max_index, max) = errors
+        .iter()
+        .enumerate()
+        .filter_map(|(i, error)| Some((i, (*error)?)))
+        .fold((0, 0.0), |a, b| if b.1 > a.1 { b } else { a });
+
+    let finite = errors.iter().flatten();
+    let finite_count = finite.clone().count();
+    let sum = finite.map(|&error| error as f64).sum::<f64>();
+
+    let pixels = errors
+        .iter()
+        .map(|error| match error {
+            Some(error) if max > 0.0 => egui::Color32::from_gray((error / max * 255.0) as u8),
+            Some(_) => egui::Color32::BLACK,
+            None => colormap::NON_FINITE_COLOR,
+        })
+        .collect();
+
+    let texture = ctx.load_texture(
+        "simd difference",
+        egui::ColorImage {
+            size: [size; 2],
+            pixels,
+        },
+        egui::TextureOptions::NEAREST,
+    );
+
+    Ok(Diff {
+        max,
+        max_at: [max_index % size.max(1), max_index / size.max(1)],
+        mean: if finite_count == 0 {
+            0.0
+        } else {
+            sum / finite_count as f64
+        },
+        differing: scalar
+            .iter()
+            .zip(simd)
+            .filter(|(a, b)| a.to_bits() != b.to_bits())
+            .count(),
+        non_finite_mismatches: errors.len() - finite_count,
+        count: errors.len(),
+        texture,
+    })
+}
+
+impl Diff {
+    fn ui(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("simd diff").num_columns(2).show(ui, |ui| {
+            ui.label("max error");
+            ui.monospace(format!(
+                "{:e} at {}, {}",
+                self.max, self.max_at[0], self.max_at[1]
+            ));
+            ui.end_row();
+
+            ui.label("mean error");
+            ui.monospace(format!("{:e}", self.mean));
+            ui.end_row();
+
+            ui.label("differing");
+            ui.monospace(format!(
+                "{} of {} ({:.2}%)",
+                self.differing,
+                self.count,
+                self.differing as f64 / self.count.max(1) as f64 * 100.0
+            ));
+            ui.end_row();
+
+            ui.label("finite mismatch");
+            ui.monospace(self.non_finite_mismatches.to_string());
+            ui.end_row();
+        });
+
+        if self.differing == 0 {
+            ui.label("Both samplers return identical values.");
+        } else {
+            ui.weak(
+                "Brightness is relative to the max error, \
+                magenta marks NaN or infinite values in only one of them.",
+            );
+        }
+
+        ui.add(
+            egui::Image::new(egui::load::SizedTexture::new(
+                self.texture.id(),
+                egui::Vec2::splat(IMAGE_SIZE),
+            ))
+            .fit_to_exact_size(egui::Vec2::splat(IMAGE_SIZE)),
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Value statistics and a histogram of a render.
+
+use eframe::egui;
+
+const HISTOGRAM_BINS: usize = 64;
+
+pub struct Stats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Number of finite samples below -1.
+    pub below: usize,
+    /// Number of finite samples above 1.
+    pub above: usize,
+    /// Number of NaN and infinite samples.
+    pub non_finite: usize,
+    pub count: usize,
+    /// The range the histogram covers, always includes `-1..=1`.
+    pub histogram_range: (f32, f32),
+    pub histogram: [usize; HISTOGRAM_BINS],
+    /// Number of finite samples above each threshold.
+    pub above_thresholds: Vec<(f32, usize)>,
+}
+
+impl Stats {
+    /// Also counts the samples above each of the `thresholds`.
+    pub fn new(values: &[f32], thresholds: &[f32]) -> Self {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        let mut finite = 0;
+
+        for &value in values.iter().filter(|value| value.is_finite()) {
+            min = min.min(value);
+            max = max.max(value);
+            sum += value as f64;
+            sum_sq += value as f64 * value as f64;
+            finite += 1;
+        }
+
+        let (mean, std_dev) = if finite == 0 {
+            (f64::NAN, f64::NAN)
+        } else {
+            let mean = sum / finite as f64;
+            let variance = (sum_sq / finite as f64 - mean * mean).max(0.0);
+            (mean, variance.sqrt())
+        };
+
+        let lo = min.min(-1.0);
+        let hi = max.max(1.0);
+        let mut histogram = [0; HISTOGRAM_BINS];
+        let mut below = 0;
+        let mut above = 0;
+
+        for &value in values.iter().filter(|value| value.is_finite()) {
+            below += (value < -1.0) as usize;
+            above += (value > 1.0) as usize;
+
+            let t = (value - lo) / (hi - lo);
+            let bin = ((t * HISTOGRAM_BINS as f32) as usize).min(HISTOGRAM_BINS - 1);
+            histogram[bin] += 1;
+        }
+
+        let above_thresholds = thresholds
+            .iter()
+            .map(|&threshold| {
+                let count = values.iter().filter(|&&value| value > threshold).count();
+                (threshold, count)
+            })
+            .collect();
+
+        Self {
+            min,
+            max,
+            mean,
+            std_dev,
+            below,
+            above,
+            non_finite: values.len() - finite,
+            count: values.len(),
+            histogram_range: (lo, hi),
+            histogram,
+            above_thresholds,
+        }
+    }
+
+    pub fn out_of_range(&self) -> usize {
+        self.below + self.above + self.non_finite
+    }
+
+    pub fn ui(&self, ui: &mut egui::Ui, width: f32) {
+        if self.count == 0 {
+            return;
+        }
+
+        egui::Grid::new("stats").num_columns(4).show(ui, |ui| {
+            ui.label("min");
+            ui.monospace(format!("{:.4}", self.min));
+            ui.label("max");
+            ui.monospace(format!("{:.4}", self.max));
+            ui.end_row();
+
+            ui.label("mean");
+            ui.monospace(format!("{:.4}", self.mean));
+            ui.label("std dev");
+            ui.monospace(format!("{:.4}", self.std_dev));
+            ui.end_row();
+        });
+
+        if !self.above_thresholds.is_empty() {
+            egui::Grid::new("above thresholds")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for &(threshold, count) in &self.above_thresholds {
+                        let percent = count as f64 / self.count as f64 * 100.0;
+                        ui.label(format!("above {threshold}"));
+                        ui.monospace(format!("{percent:.2}%"));
+                        ui.end_row();
+                    }
+                });
+        }
+
+        let out_of_range = self.out_of_range();
+
+        if out_of_range != 0 {
+            let percent = out_of_range as f64 / self.count as f64 * 100.0;
+
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "⚠ {out_of_range} samples ({percent:.2}%) outside of [-1, 1]: \
+                    {} below, {} above, {} NaN or infinite",
+                    self.below, self.above, self.non_finite
+                ),
+            );
+        }
+
+        self.histogram_ui(ui, width);
+    }
+
+    fn histogram_ui(&self, ui: &mut egui::Ui, width: f32) {
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(width, 80.0), egui::Sense::hover());
+
+        let rect = response.rect;
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+        let (lo, hi) = self.histogram_range;
+        let x_of = |value: f32| rect.left() + (value - lo) / (hi - lo) * rect.width();
+        let bin_width = (hi - lo) / HISTOGRAM_BINS as f32;
+        let max_count = self.histogram.iter().copied().max().unwrap_or(0).max(1);
+
+        for (i, &count) in self.histogram.iter().enumerate() {
+            let start = lo + i as f32 * bin_width;
+            let end = start + bin_width;
+            let height = count as f32 / max_count as f32 * rect.height();
+
+            let in_range = start >= -1.0 && end <= 1.0;
+            let color = if in_range {
+                visuals.text_color()
+            } else {
+                visuals.warn_fg_color
+            };
+
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(x_of(start), rect.bottom() - height),
+                    egui::pos2(x_of(end), rect.bottom()),
+                ),
+                0.0,
+                color,
+            );
+        }
+
+        for mark in [-1.0, 0.0, 1.0] {
+            painter.vline(
+                x_of(mark),
+                rect.y_range(),
+                egui::Stroke::new(1.0_f32, visuals.weak_text_color()),
+            );
+        }
+
+        if let Some(pos) = response.hover_pos() {
+            let i = (((pos.x - rect.left()) / rect.width() * HISTOGRAM_BINS as f32) as usize)
+                .min(HISTOGRAM_BINS - 1);
+            let start = lo + i as f32 * bin_width;
+
+            response.on_hover_text_at_pointer(format!(
+                "{start:.3}..{:.3}: {} samples",
+                start + bin_width,
+                self.histogram[i]
+            ));
+        }
+    }
+}
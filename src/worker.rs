@@ -0,0 +1,369 @@
+//! Samples images on web workers so the web version renders off the main thread.
+//!
+//! There is a worker for each core and the batches of a render are split across them unless
+//! `settings.multithreaded` is turned off. Each worker runs [`run`] from the `worker` binary that trunk builds next to the app, see
+//! `index.html`. Wasm threads would need shared memory, which needs a cross-origin isolated
+//! page, so instead a worker is sent the settings and a batch of rows and sends back the
+//! sampled values. Batches that aren't sent yet are dropped when a render is canceled.
+//!
+//! If the workers can't be started the rows are sampled on the main thread in small batches
+//! each frame instead.
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use eframe::wasm_bindgen::{JsCast as _, JsValue, closure::Closure};
+use web_time::{Duration, Instant};
+
+use crate::{
+    sampling::{self, Progress},
+    settings::{DEFAULT_SETTINGS, Settings},
+};
+
+/// The loader of the `worker` binary that trunk generates.
+const WORKER_SCRIPT: &str = "./worker_loader.js";
+
+/// Number of rows sent to a worker at once.
+const BATCH_ROWS: usize = 16;
+
+/// How long to sample each frame when there are no workers.
+const FRAME_BUDGET: Duration = Duration::from_millis(8);
+
+thread_local! {
+    static POOL: RefCell<Pool> = RefCell::new(Pool::new());
+}
+
+/// Entry point of the worker, answers each batch with its values.
+pub fn run() {
+    let scope = js_sys::global().unchecked_into::<web_sys::DedicatedWorkerGlobalScope>();
+    let reply_to = scope.clone();
+
+    let onmessage =
+        Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |event: web_sys::MessageEvent| {
+            let (success, values) = sample_batch(&js_sys::Array::from(&event.data()));
+            let values = js_sys::Float32Array::from(&values[..]);
+            let reply = js_sys::Array::of2(&JsValue::from_bool(success), &values);
+
+            // move the values instead of copying them
+            reply_to
+                .post_message_with_transfer(&reply, &js_sys::Array::of1(&values.buffer()))
+                .expect("the values can be transferred");
+        });
+
+    scope.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+
+    // the handler lives as long as the worker
+    onmessage.forget();
+
+    // messages that arrive while the wasm loads are dropped, so the pool waits for this
+    scope
+        .post_message(&JsValue::NULL)
+        .expect("null can be sent");
+}
+
+/// Samples a batch sent by [`Pool::dispatch`].
+fn sample_batch(message: &js_sys::Array) -> (bool, Vec<f32>) {
+    let number = |index| message.get(index).as_f64().unwrap_or_default() as usize;
+    let [size, first_row, rows] = [1, 2, 3].map(number);
+
+    let settings = message
+        .get(0)
+        .as_string()
+        .and_then(|fragment| Settings::from_url_fragment(&fragment).ok())
+        .expect("the app sends valid settings");
+
+    let mut values = vec![0.0; rows * size];
+    let success = sampling::sample_rows(
+        &settings,
+        size,
+        first_row,
+        &mut values,
+        &Progress::default(),
+    );
+
+    (success, values)
+}
+
+/// Samples a `size * size` image on the workers. Dropping it cancels the rows that aren't sent
+/// to a worker yet.
+pub struct Sampling {
+    job: Rc<RefCell<Job>>,
+}
+
+struct Job {
+    settings: Settings,
+    /// The settings in the format the workers read.
+    fragment: String,
+    size: usize,
+    values: Vec<f32>,
+    /// The first row and number of rows of the batches that aren't sent yet, the next one last.
+    pending: Vec<[usize; 2]>,
+    /// Number of batches the workers are sampling.
+    in_flight: usize,
+    /// Number of batches that may be sampled at once.
+    threads: usize,
+    rows: usize,
+    success: bool,
+}
+
+impl Sampling {
+    pub fn start(settings: &Settings) -> Self {
+        let size = settings.texture_size;
+
+        // the size is sent with each batch, exports are larger than `texture_size` allows
+        let fragment = Settings {
+            texture_size: DEFAULT_SETTINGS.texture_size,
+            ..settings.clone()
+        }
+        .to_url_fragment();
+
+        let job = Rc::new(RefCell::new(Job {
+            settings: settings.clone(),
+            fragment,
+            size,
+            values: vec![0.0; size * size],
+            pending: (0..size)
+                .step_by(BATCH_ROWS)
+                .rev()
+                .map(|first_row| [first_row, BATCH_ROWS.min(size - first_row)])
+                .collect(),
+            in_flight: 0,
+            threads: POOL.with(|pool| pool.borrow().threads(settings)),
+            rows: 0,
+            success: true,
+        }));
+
+        POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+            pool.jobs.push(Rc::downgrade(&job));
+            pool.dispatch();
+        });
+
+        Self { job }
+    }
+
+    /// Returns the number of sampled rows.
+    pub fn rows(&self) -> usize {
+        self.job.borrow().rows
+    }
+
+    /// Returns the number of workers that sample the image.
+    pub fn threads(&self) -> usize {
+        self.job.borrow().threads
+    }
+
+    /// Returns the values once all rows are sampled and whether the noise type supports the
+    /// dimension / tileable combination.
+    pub fn poll(&mut self) -> Option<(Vec<f32>, bool)> {
+        let mut job = self.job.borrow_mut();
+
+        if !POOL.with(|pool| pool.borrow().has_workers()) {
+            job.sample_on_main_thread();
+        }
+
+        (job.rows == job.size).then(|| (std::mem::take(&mut job.values), job.success))
+    }
+}
+
+impl Job {
+    /// Samples the pending batches until the frame budget is used up.
+    fn sample_on_main_thread(&mut self) {
+        let start = Instant::now();
+
+        while start.elapsed() < FRAME_BUDGET
+            && let Some([first_row, rows]) = self.pending.pop()
+        {
+            let values = &mut self.values[first_row * self.size..(first_row + rows) * self.size];
+
+            self.success &= sampling::sample_rows(
+                &self.settings,
+                self.size,
+                first_row,
+                values,
+                &Progress::default(),
+            );
+            self.rows += rows;
+        }
+    }
+}
+
+struct Pool {
+    workers: Vec<Worker>,
+    /// The jobs that aren't done, oldest first.
+    jobs: Vec<Weak<RefCell<Job>>>,
+}
+
+struct Worker {
+    worker: web_sys::Worker,
+    /// The job and batch the worker is sampling.
+    batch: Option<(Weak<RefCell<Job>>, [usize; 2])>,
+    /// Set once the worker can receive batches, see [`run`].
+    ready: bool,
+    failed: bool,
+    _onmessage: Closure<dyn Fn(web_sys::MessageEvent)>,
+    _onerror: Closure<dyn Fn(web_sys::Event)>,
+}
+
+impl Pool {
+    fn new() -> Self {
+        let cores = web_sys::window()
+            .map_or(1, |window| {
+                window.navigator().hardware_concurrency() as usize
+            })
+            .max(1);
+
+        // the index of a worker is its position
+        let workers: Vec<_> = (0..cores)
+            .map_while(|index| {
+                Worker::spawn(index)
+                    .inspect_err(|e| log::warn!("failed to start a worker: {e:?}"))
+                    .ok()
+            })
+            .collect();
+
+        if workers.is_empty() {
+            log::warn!("no workers, sampling on the main thread");
+        }
+
+        Self {
+            workers,
+            jobs: Vec::new(),
+        }
+    }
+
+    fn has_workers(&self) -> bool {
+        self.workers.iter().any(|worker| !worker.failed)
+    }
+
+    /// Returns the number of workers to sample `settings` with.
+    fn threads(&self, settings: &Settings) -> usize {
+        if settings.multithreaded {
+            self.workers
+                .iter()
+                .filter(|worker| !worker.failed)
+                .count()
+                .max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Sends the next batches to the idle workers.
+    fn dispatch(&mut self) {
+        self.jobs.retain(|job| {
+            job.upgrade()
+                .is_some_and(|job| job.borrow().rows < job.borrow().size)
+        });
+
+        for worker in &mut self.workers {
+            if !worker.ready || worker.failed || worker.batch.is_some() {
+                continue;
+            }
+
+            let Some(job) = self.jobs.iter().filter_map(Weak::upgrade).find(|job| {
+                let job = job.borrow();
+                !job.pending.is_empty() && job.in_flight < job.threads
+            }) else {
+                return;
+            };
+
+            let mut state = job.borrow_mut();
+            let batch = state.pending.pop().expect("the job has pending batches");
+            let [first_row, rows] = batch;
+
+            let message = js_sys::Array::of4(
+                &JsValue::from_str(&state.fragment),
+                &JsValue::from(state.size as f64),
+                &JsValue::from(first_row as f64),
+                &JsValue::from(rows as f64),
+            );
+
+            match worker.worker.post_message(&message) {
+                Ok(()) => {
+                    state.in_flight += 1;
+                    worker.batch = Some((Rc::downgrade(&job), batch));
+                }
+                Err(e) => {
+                    log::warn!("failed to send a batch to a worker: {e:?}");
+                    state.pending.push(batch);
+                    worker.failed = true;
+                }
+            }
+        }
+    }
+
+    /// Marks the worker as ready or copies the values it sent back into its job.
+    fn receive(&mut self, index: usize, message: JsValue) {
+        let worker = &mut self.workers[index];
+
+        if message.is_null() {
+            worker.ready = true;
+        } else if let Some((job, [first_row, rows])) = worker.batch.take()
+            && let Some(job) = job.upgrade()
+        {
+            let message = js_sys::Array::from(&message);
+            let mut job = job.borrow_mut();
+            let size = job.size;
+
+            message
+                .get(1)
+                .unchecked_into::<js_sys::Float32Array>()
+                .copy_to(&mut job.values[first_row * size..(first_row + rows) * size]);
+
+            job.success &= message.get(0).as_bool().unwrap_or(false);
+            job.rows += rows;
+            job.in_flight -= 1;
+        }
+
+        self.dispatch();
+    }
+
+    /// Gives the batch of a worker that failed to the others, or to the main thread if there
+    /// are none left.
+    fn fail(&mut self, index: usize) {
+        log::warn!("a worker failed, its batches are sampled elsewhere");
+
+        let worker = &mut self.workers[index];
+        worker.worker.terminate();
+        worker.failed = true;
+
+        if let Some((job, batch)) = worker.batch.take()
+            && let Some(job) = job.upgrade()
+        {
+            let mut job = job.borrow_mut();
+            job.pending.push(batch);
+            job.in_flight -= 1;
+        }
+
+        self.dispatch();
+    }
+}
+
+impl Worker {
+    fn spawn(index: usize) -> Result<Self, JsValue> {
+        let worker = web_sys::Worker::new(WORKER_SCRIPT)?;
+
+        let onmessage =
+            Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |event: web_sys::MessageEvent| {
+                POOL.with(|pool| pool.borrow_mut().receive(index, event.data()));
+            });
+
+        let onerror = Closure::<dyn Fn(web_sys::Event)>::new(move |_| {
+            POOL.with(|pool| pool.borrow_mut().fail(index));
+        });
+
+        worker.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        worker.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+
+        Ok(Self {
+            worker,
+            batch: None,
+            ready: false,
+            failed: false,
+            _onmessage: onmessage,
+            _onerror: onerror,
+        })
+    }
+}